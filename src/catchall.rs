@@ -0,0 +1,121 @@
+use crate::{AnswerOption, Bank, Category, Sentence};
+use serde::{Deserialize, Serialize};
+
+pub const CATCH_ALL_FIELDS: [&str; 6] = [
+    "Add a catch-all answer at export (yes/no)",
+    "Feedback",
+    "Feedback for nouns",
+    "Feedback for verbs",
+    "Feedback for adjectives",
+    "Feedback for participles",
+];
+
+// A final `*` answer worth nothing, added to short answer and numerical
+// gaps when they are exported so that every wrong response gets a hint.
+// It is never stored with the question's own answers.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CatchAll {
+    pub enabled: bool,
+    pub feedback: String,
+    // One for each of `Category::ALL`, in order.
+    pub category_feedback: [String; 4],
+}
+
+impl Default for CatchAll {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            feedback: "Try again!".to_string(),
+            category_feedback: [
+                "Look at your notes on nouns.".to_string(),
+                "Look at your notes on verbs.".to_string(),
+                "Look at your notes on adjectives.".to_string(),
+                "Look at your notes on participles.".to_string(),
+            ],
+        }
+    }
+}
+
+impl CatchAll {
+    pub fn show(&self, field: usize) -> String {
+        match field {
+            0 => if self.enabled { "yes" } else { "no" }.to_string(),
+            1 => self.feedback.clone(),
+            _ => self.category_feedback[field - 2].clone(),
+        }
+    }
+
+    pub fn set(&mut self, field: usize, text: &str) -> Result<(), String> {
+        let text = text.trim().to_string();
+        match field {
+            0 => match text.to_lowercase().as_str() {
+                "yes" | "y" => self.enabled = true,
+                "no" | "n" => self.enabled = false,
+                _ => return Err("Please answer yes or no.".to_string()),
+            },
+            1 => self.feedback = text,
+            2..=5 => self.category_feedback[field - 2] = text,
+            _ => return Err("There is no such setting.".to_string()),
+        }
+        Ok(())
+    }
+}
+
+pub fn print_catch_all(catch_all: &CatchAll) {
+    for (num, label) in CATCH_ALL_FIELDS.iter().enumerate() {
+        println!("{}. {}: {}", num + 1, label, catch_all.show(num));
+    }
+    println!();
+}
+
+// The answer to add to a gap, if it needs one. Multiple choice gaps
+// and gaps that already end in their own `*` are left alone.
+pub fn catch_all_option(bank: &Bank, sentence: &Sentence, idx: usize) -> Option<AnswerOption> {
+    let group = &sentence.answers[idx];
+    if !bank.catch_all.enabled
+        || !group.first().is_some_and(|answer| answer.is_question)
+        || sentence
+            .gap_type(idx, bank.default_gap_type)
+            .is_multichoice()
+        || group.iter().any(|answer| answer.answer.trim() == "*")
+    {
+        return None;
+    }
+    let category = sentence
+        .chunk_settings
+        .get(idx)
+        .and_then(|settings| settings.category);
+    let feedback = match category {
+        Some(category) => {
+            let pos = Category::ALL
+                .iter()
+                .position(|c| *c == category)
+                .unwrap_or(0);
+            &bank.catch_all.category_feedback[pos]
+        }
+        None => &bank.catch_all.feedback,
+    };
+    Some(AnswerOption {
+        is_question: true,
+        mark: 0,
+        answer: "*".to_string(),
+        feedback: feedback.clone(),
+    })
+}
+
+pub fn print_catch_all_preview(bank: &Bank, sentence: &Sentence, idx: usize) {
+    if let Some(option) = catch_all_option(bank, sentence, idx) {
+        println!(
+            "Added at export: {}. Mark {}. Feedback: {}",
+            option.answer, option.mark, option.feedback
+        );
+        println!();
+    }
+}
+
+// A gap's answers as they go into an export.
+pub fn exported_answers(bank: &Bank, sentence: &Sentence, idx: usize) -> Vec<AnswerOption> {
+    let mut answers = sentence.answers[idx].clone();
+    answers.extend(catch_all_option(bank, sentence, idx));
+    answers
+}
@@ -0,0 +1,141 @@
+use crate::catchall::exported_answers;
+use crate::metadata::FIELDS;
+use crate::printing::{announce, print_boxed, print_key_help};
+use crate::{read_input, Bank, Sentence};
+
+// Each format remembers its own last export, since exporting to one
+// leaves the other out of date.
+#[derive(Clone, Copy)]
+pub enum Format {
+    Xml,
+    Gift,
+}
+
+impl Format {
+    fn hash(self, question: &Sentence) -> Option<u64> {
+        match self {
+            Format::Xml => question.exported_hash,
+            Format::Gift => question.gift_hash,
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum Change {
+    New,
+    Modified,
+    Unchanged,
+}
+
+// FNV-1a, which unlike the standard library's hasher gives the same
+// result from one build to the next.
+fn fnv1a(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+// Covers everything that ends up in an export, including the bank
+// defaults the question falls back on.
+pub fn content_hash(question: &Sentence, bank: &Bank) -> u64 {
+    let exercise = question
+        .exercise
+        .and_then(|idx| bank.exercises.get(idx))
+        .map_or(String::new(), |exercise| {
+            format!("{}/{}", exercise.stage, exercise.name)
+        });
+    let mut content = format!("{}\n{}\n", question.initial_sentence, exercise);
+    for num in 0..question.answers.len() {
+        content.push_str(&format!(
+            "{}:{}\n",
+            question.gap_type(num, bank.default_gap_type).code(),
+            question.gap_weight(num)
+        ));
+        for option in exported_answers(bank, question, num) {
+            content.push_str(&format!(
+                "{}|{}|{}|{}\n",
+                option.is_question, option.mark, option.answer, option.feedback
+            ));
+        }
+    }
+    let metadata = question.metadata.merged(&bank.defaults);
+    for field in 0..FIELDS.len() {
+        content.push_str(&format!("{:?}\n", metadata.show(field)));
+    }
+    fnv1a(&content)
+}
+
+fn change(question: &Sentence, bank: &Bank, format: Format) -> Change {
+    match format.hash(question) {
+        None => Change::New,
+        Some(hash) if hash != content_hash(question, bank) => Change::Modified,
+        Some(_) => Change::Unchanged,
+    }
+}
+
+// Returns the IDs of the questions to export. When some questions have
+// not changed since the last export the author sees what has and may
+// export just those.
+pub fn choose_questions(bank: &Bank, format: Format) -> Vec<u32> {
+    let changes: Vec<Change> = bank
+        .questions
+        .iter()
+        .map(|question| change(question, bank, format))
+        .collect();
+    let all: Vec<u32> = bank.questions.iter().map(|question| question.id).collect();
+    if !changes.contains(&Change::Unchanged) {
+        return all;
+    }
+
+    let mut summary = Vec::new();
+    let mut changed = Vec::new();
+    for (num, (question, change)) in bank.questions.iter().zip(&changes).enumerate() {
+        let label = match change {
+            Change::New => "New",
+            Change::Modified => "Changed",
+            Change::Unchanged => continue,
+        };
+        summary.push(format!(
+            "{}: {}. {}",
+            label,
+            num + 1,
+            question.initial_sentence
+        ));
+        changed.push(question.id);
+    }
+    if changed.is_empty() {
+        summary.push("Nothing has changed since the last export.".to_string());
+    }
+    let unchanged = changes.len() - changed.len();
+    summary.push(format!("{} questions are unchanged.", unchanged));
+    print_boxed(&summary.join("\n"));
+    print_key_help(&[("c", "Changed only"), ("a", "All questions")]);
+    match read_input().as_str() {
+        "a" => all,
+        _ => {
+            if changed.is_empty() {
+                announce("No questions to export.");
+            }
+            changed
+        }
+    }
+}
+
+pub fn mark_exported(bank: &mut Bank, ids: &[u32], format: Format) {
+    let hashes: Vec<Option<u64>> = bank
+        .questions
+        .iter()
+        .map(|question| Some(content_hash(question, bank)).filter(|_| ids.contains(&question.id)))
+        .collect();
+    for (question, hash) in bank.questions.iter_mut().zip(hashes) {
+        if hash.is_some() {
+            match format {
+                Format::Xml => question.exported_hash = hash,
+                Format::Gift => question.gift_hash = hash,
+            }
+        }
+    }
+}
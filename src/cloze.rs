@@ -0,0 +1,297 @@
+use crate::theme::{mark_role, paint, Role};
+use crate::AnswerOption;
+use serde::{Deserialize, Serialize};
+
+const SPECIAL: [char; 4] = ['}', '~', '#', '\\'];
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if SPECIAL.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+// Splits on `sep` wherever it is not preceded by a backslash.
+fn split_unescaped(text: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (pos, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if c == sep => {
+                parts.push(&text[start..pos]);
+                start = pos + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ClozeType {
+    ShortAnswer,
+    ShortAnswerCase,
+    MultiChoice,
+    MultiChoiceHorizontal,
+    MultiChoiceVertical,
+    MultiChoiceShuffled,
+    Numerical,
+}
+
+impl ClozeType {
+    pub const ALL: [ClozeType; 7] = [
+        ClozeType::ShortAnswer,
+        ClozeType::ShortAnswerCase,
+        ClozeType::MultiChoice,
+        ClozeType::MultiChoiceHorizontal,
+        ClozeType::MultiChoiceVertical,
+        ClozeType::MultiChoiceShuffled,
+        ClozeType::Numerical,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            ClozeType::ShortAnswer => "SHORTANSWER",
+            ClozeType::ShortAnswerCase => "SHORTANSWER_C",
+            ClozeType::MultiChoice => "MULTICHOICE",
+            ClozeType::MultiChoiceHorizontal => "MULTICHOICE_H",
+            ClozeType::MultiChoiceVertical => "MULTICHOICE_V",
+            ClozeType::MultiChoiceShuffled => "MULTICHOICE_S",
+            ClozeType::Numerical => "NUMERICAL",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ClozeType::ShortAnswer => "Short answer",
+            ClozeType::ShortAnswerCase => "Short answer (case-sensitive)",
+            ClozeType::MultiChoice => "Dropdown",
+            ClozeType::MultiChoiceHorizontal => "Radio buttons (across)",
+            ClozeType::MultiChoiceVertical => "Radio buttons (down)",
+            ClozeType::MultiChoiceShuffled => "Dropdown (shuffled)",
+            ClozeType::Numerical => "Numerical",
+        }
+    }
+
+    // Moodle also accepts the short forms, e.g. `{1:MC:...}`.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_uppercase().as_str() {
+            "SHORTANSWER" | "SA" | "MW" => Some(ClozeType::ShortAnswer),
+            "SHORTANSWER_C" | "SAC" | "MWC" => Some(ClozeType::ShortAnswerCase),
+            "MULTICHOICE" | "MC" => Some(ClozeType::MultiChoice),
+            "MULTICHOICE_H" | "MCH" => Some(ClozeType::MultiChoiceHorizontal),
+            "MULTICHOICE_V" | "MCV" => Some(ClozeType::MultiChoiceVertical),
+            "MULTICHOICE_S" | "MCS" => Some(ClozeType::MultiChoiceShuffled),
+            "NUMERICAL" | "NM" => Some(ClozeType::Numerical),
+            _ => None,
+        }
+    }
+
+    pub fn is_multichoice(self) -> bool {
+        matches!(
+            self,
+            ClozeType::MultiChoice
+                | ClozeType::MultiChoiceHorizontal
+                | ClozeType::MultiChoiceVertical
+                | ClozeType::MultiChoiceShuffled
+        )
+    }
+}
+
+pub fn moodle_subquestion(kind: ClozeType, weight: u32, answers: &[AnswerOption]) -> String {
+    let mut question_string = String::new();
+    for answer in answers {
+        question_string = format!(
+            "{}~%{}%{}#{}",
+            question_string,
+            answer.mark,
+            escape(&answer.answer),
+            escape(&answer.feedback)
+        );
+    }
+    format!("{{{}:{}:{}}}", weight, kind.code(), question_string)
+}
+
+// Reads back a single `{n:TYPE:...}` subquestion as typed by the author.
+// Moodle treats a missing weight as 1.
+pub fn parse_cloze(code: &str) -> Option<(u32, ClozeType, Vec<AnswerOption>)> {
+    let inner = code.trim().strip_prefix('{')?.strip_suffix('}')?;
+    let mut header = inner.splitn(3, ':');
+    let weight = match header.next()?.trim() {
+        "" => 1,
+        weight => weight.parse::<u32>().ok().filter(|weight| *weight > 0)?,
+    };
+    let kind = ClozeType::from_code(header.next()?)?;
+    let body = header.next()?;
+
+    let mut options = Vec::new();
+    for raw in split_unescaped(body, '~') {
+        if raw.trim().is_empty() {
+            continue;
+        }
+        let (mark, rest) = if let Some(rest) = raw.strip_prefix('=') {
+            (100, rest)
+        } else if let Some(rest) = raw.strip_prefix('%') {
+            let end = rest.find('%')?;
+            let mark = rest[..end].trim().parse::<f32>().ok()?;
+            if !(0.0..=100.0).contains(&mark) {
+                return None;
+            }
+            (mark.round() as u8, &rest[end + 1..])
+        } else {
+            (0, raw)
+        };
+        let mut parts = split_unescaped(rest, '#').into_iter();
+        let answer = unescape(parts.next().unwrap_or_default());
+        let feedback: Vec<&str> = parts.collect();
+        options.push(AnswerOption {
+            is_question: true,
+            mark,
+            answer,
+            feedback: unescape(&feedback.join("#")),
+        });
+    }
+    if options.is_empty() {
+        None
+    } else {
+        Some((weight, kind, options))
+    }
+}
+
+pub fn highlight_cloze(line: &str) -> String {
+    let mut out = String::new();
+    let mut token = String::new();
+    let mut role: Option<Role> = None;
+    let mut chars = line.chars().peekable();
+    let mut in_header = false;
+    let mut colons = 0;
+
+    macro_rules! flush {
+        () => {
+            match role {
+                Some(role) => out.push_str(&paint(role, &token)),
+                None => out.push_str(&token),
+            }
+            token.clear();
+        };
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                token.push(c);
+                token.extend(chars.next());
+            }
+            '{' => {
+                flush!();
+                in_header = true;
+                colons = 0;
+                role = Some(Role::Syntax);
+                token.push(c);
+            }
+            ':' if in_header => {
+                token.push(c);
+                colons += 1;
+                if colons == 2 {
+                    in_header = false;
+                    flush!();
+                    role = None;
+                }
+            }
+            '}' => {
+                flush!();
+                out.push_str(&paint(Role::Syntax, "}"));
+                role = None;
+            }
+            '~' => {
+                flush!();
+                out.push_str(&paint(Role::Separator, "~"));
+                role = None;
+            }
+            '=' if token.is_empty() && role.is_none() => {
+                out.push_str(&paint(Role::FullMark, "="));
+            }
+            '%' if token.is_empty() && role.is_none() => {
+                let mut mark = String::from("%");
+                while let Some(&next) = chars.peek() {
+                    mark.push(next);
+                    chars.next();
+                    if next == '%' {
+                        break;
+                    }
+                }
+                let value = mark.trim_matches('%').parse::<f32>().unwrap_or(0.0);
+                out.push_str(&paint(mark_role(value.round() as u8), &mark));
+            }
+            '#' if !in_header => {
+                flush!();
+                role = Some(Role::Feedback);
+                token.push(c);
+            }
+            _ => token.push(c),
+        }
+    }
+    flush!();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_weight_type_and_options() {
+        let (weight, kind, options) =
+            parse_cloze("{2:SAC:=λόγος#Good~%50%λογος#Accents~*#Try again!}").unwrap();
+        assert_eq!(weight, 2);
+        assert_eq!(kind, ClozeType::ShortAnswerCase);
+        let marks: Vec<u8> = options.iter().map(|option| option.mark).collect();
+        assert_eq!(marks, [100, 50, 0]);
+        assert_eq!(options[1].answer, "λογος");
+        assert_eq!(options[2].feedback, "Try again!");
+    }
+
+    #[test]
+    fn a_missing_weight_is_one() {
+        let (weight, kind, _) = parse_cloze("{:NUMERICAL:=5:0.5}").unwrap();
+        assert_eq!(weight, 1);
+        assert_eq!(kind, ClozeType::Numerical);
+    }
+
+    #[test]
+    fn escaped_separators_stay_in_the_answer() {
+        let (_, _, options) = parse_cloze("{1:SA:=a\\~b\\#c#fine}").unwrap();
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].answer, "a~b#c");
+        assert_eq!(options[0].feedback, "fine");
+    }
+
+    #[test]
+    fn rejects_bad_codes() {
+        assert!(parse_cloze("1:SA:=a").is_none());
+        assert!(parse_cloze("{1:XX:=a}").is_none());
+        assert!(parse_cloze("{0:SA:=a}").is_none());
+        assert!(parse_cloze("{1:SA:%150%a}").is_none());
+        assert!(parse_cloze("{1:SA:}").is_none());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::punctuation::Language;
+use crate::template::Templates;
+use crate::theme::Theme;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+// Settings come from ~/.string_tweaker.conf (`key = value` lines) and
+// are then overridden by command line flags.
+pub struct Config {
+    pub tui: bool,
+    pub plain: bool,
+    pub color: bool,
+    pub theme: Theme,
+    pub templates: Templates,
+    pub language: Language,
+    pub bank_path: PathBuf,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+fn config_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".string_tweaker.conf"))
+}
+
+fn read_settings() -> Vec<(String, String)> {
+    let contents = match config_path().and_then(|path| fs::read_to_string(path).ok()) {
+        Some(contents) => contents,
+        None => return Vec::new(),
+    };
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+fn is_true(value: &str) -> bool {
+    matches!(value, "true" | "yes" | "on" | "1")
+}
+
+impl Config {
+    fn load() -> Self {
+        let mut config = Config {
+            tui: false,
+            plain: false,
+            color: true,
+            theme: Theme::named("default").expect("default theme exists"),
+            templates: Templates::default(),
+            language: Language::named("greek").expect("greek profile exists"),
+            bank_path: PathBuf::from("bank.json"),
+        };
+        let settings = read_settings();
+        let mut theme_name: Option<String> = None;
+        let mut language_name: Option<String> = None;
+        for (key, value) in settings.iter() {
+            match key.as_str() {
+                "tui" => config.tui = is_true(value),
+                "plain" => config.plain = is_true(value),
+                "color" => config.color = is_true(value),
+                "theme" => theme_name = Some(value.clone()),
+                "language" => language_name = Some(value.clone()),
+                "bank" => config.bank_path = PathBuf::from(value),
+                _ => {}
+            }
+        }
+        for arg in env::args().skip(1) {
+            match arg.as_str() {
+                "--tui" => config.tui = true,
+                "--plain" => config.plain = true,
+                "--no-color" => config.color = false,
+                _ => {
+                    if let Some(name) = arg.strip_prefix("--theme=") {
+                        theme_name = Some(name.to_string());
+                    }
+                    if let Some(name) = arg.strip_prefix("--language=") {
+                        language_name = Some(name.to_string());
+                    }
+                    if let Some(path) = arg.strip_prefix("--bank=") {
+                        config.bank_path = PathBuf::from(path);
+                    }
+                }
+            }
+        }
+        if let Some(theme) = theme_name.as_deref().and_then(Theme::named) {
+            config.theme = theme;
+        }
+        if let Some(language) = language_name.as_deref().and_then(Language::named) {
+            config.language = language;
+        }
+        // Individual colours and punctuation marks may be overridden on
+        // top of the chosen theme and language, and export templates
+        // replace the built-in layout.
+        for (key, value) in settings.iter() {
+            if let Some(role) = key.strip_prefix("theme.") {
+                config.theme.set(role, value);
+            }
+            if let Some(kind) = key.strip_prefix("template.") {
+                config.templates.set(kind, value);
+            }
+            if let Some(mark) = key.strip_prefix("punctuation.") {
+                config.language.set(mark, value);
+            }
+        }
+        // Logged or piped sessions get no escape codes or box art.
+        if !io::stdout().is_terminal() {
+            config.plain = true;
+        }
+        if config.plain {
+            config.tui = false;
+        }
+        if config.plain || env::var_os("NO_COLOR").is_some() {
+            config.color = false;
+        }
+        config
+    }
+}
+
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::load)
+}
@@ -0,0 +1,482 @@
+use crate::catchall::exported_answers;
+use crate::changes::{choose_questions, mark_exported, Format};
+use crate::cloze::ClozeType;
+use crate::config::config;
+use crate::grading::gaps;
+use crate::printing::{
+    announce_export, export_groups, leave_out, print_boxed, print_left_out, NO_GAPS,
+};
+use crate::punctuation::{attaches, end_mark};
+use crate::template::{category_path, question_name};
+use crate::testcases::check_before_export;
+use crate::{read_input, AnswerOption, Bank, Sentence};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+
+const SPECIAL: [char; 7] = ['~', '=', '#', '{', '}', ':', '\\'];
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if SPECIAL.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// Short answers write every option with `=`, partial and zero marks
+// carrying an explicit percentage. Multiple choice marks wrong options
+// with `~`, and numerical gaps keep their `answer:tolerance` unescaped.
+// GIFT has no case-sensitive short answer, so that falls back to the
+// ordinary kind.
+fn gift_answers(kind: ClozeType, answers: &[AnswerOption]) -> String {
+    let options: Vec<String> = answers
+        .iter()
+        .map(|answer| {
+            let weight = match (answer.mark, kind.is_multichoice()) {
+                (100, _) => "=".to_string(),
+                (0, true) => "~".to_string(),
+                (mark, true) => format!("~%{}%", mark),
+                (mark, false) => format!("=%{}%", mark),
+            };
+            let text = match kind {
+                ClozeType::Numerical => answer.answer.clone(),
+                _ => escape(&answer.answer),
+            };
+            if answer.feedback.is_empty() {
+                format!("{}{}", weight, text)
+            } else {
+                format!("{}{}#{}", weight, text, escape(&answer.feedback))
+            }
+        })
+        .collect();
+    match kind {
+        ClozeType::Numerical => format!("{{#{}}}", options.join(" ")),
+        _ => format!("{{{}}}", options.join(" ")),
+    }
+}
+
+pub fn gift_question(question: &Sentence, question_name: &str, bank: &Bank) -> String {
+    // Spacing and the closing mark are worked out on the unescaped text.
+    let mut raw: Vec<String> = Vec::new();
+    let mut text = String::new();
+    for (num, group) in question.answers.iter().enumerate() {
+        if group.is_empty() {
+            continue;
+        }
+        let part = if group[0].is_question {
+            raw.push(String::new());
+            gift_answers(
+                question.gap_type(num, bank.default_gap_type),
+                &exported_answers(bank, question, num),
+            )
+        } else {
+            raw.push(group[0].answer.clone());
+            escape(&group[0].answer)
+        };
+        if !attaches(&text, raw.last().map_or("", String::as_str)) {
+            text.push(' ');
+        }
+        text.push_str(&part);
+    }
+    format!(
+        "::{}::{}\\n{}{}\n",
+        escape(question_name),
+        escape(&question.initial_sentence),
+        text,
+        escape(end_mark(&raw, &config().language))
+    )
+}
+
+// Moodle's GIFT import reads one answer block per question and leaves
+// any later ones in the text, so questions with several gaps are left
+// out and listed instead, as are those with none.
+pub fn print_gift_file(bank: &mut Bank) {
+    let mut ids = choose_questions(bank, Format::Gift);
+    let mut left_out = leave_out(
+        bank,
+        &mut ids,
+        |question| gaps(question).is_empty(),
+        NO_GAPS,
+    );
+    left_out.extend(leave_out(
+        bank,
+        &mut ids,
+        |question| gaps(question).len() > 1,
+        "GIFT allows only one gap per question, so these were left out.\n\
+         Export them to Moodle XML instead:",
+    ));
+    print_left_out(left_out);
+    if ids.is_empty() || !check_before_export(bank, &ids) {
+        return;
+    }
+    let files = export_groups(bank, &ids, "upload", "gift");
+    for (file_name, groups) in files.iter() {
+        let f = File::create(file_name).expect("Unable to create file");
+        let mut writer = BufWriter::new(f);
+        for group in groups {
+            writeln!(
+                writer,
+                "$CATEGORY: {}\n",
+                category_path(&group.stage, &group.name)
+            )
+            .expect("Unable to write gift category.");
+
+            let mut question_number: u32 = 1000;
+            for question in group.questions.iter() {
+                question_number += 1;
+                let name = question_name(&group.stage, &group.name, question_number, question.id);
+                let text = gift_question(question, &name, bank);
+                writeln!(writer, "// question: {}", question.id)
+                    .and_then(|_| writeln!(writer, "{}", text))
+                    .expect("Unable to write gift question.");
+            }
+        }
+        writer.flush().expect("Unable to write data.");
+    }
+    announce_export(&files);
+    mark_exported(bank, &ids, Format::Gift);
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                unescaped.push('\n');
+                chars.next();
+            }
+            ('\\', Some(next)) => {
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+// Finds `pat` in `text` from byte `from`, skipping backslash escapes.
+fn find_unescaped(text: &str, from: usize, pat: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (pos, c) in text[from..].char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if text[from + pos..].starts_with(pat) {
+            return Some(from + pos);
+        }
+    }
+    None
+}
+
+fn strip_tags(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => stripped.push(c),
+            _ => {}
+        }
+    }
+    stripped
+}
+
+enum Part {
+    Text(String),
+    Gap(ClozeType, Vec<AnswerOption>),
+}
+
+fn parse_option(raw: &str) -> Result<AnswerOption, String> {
+    let (prefix, rest) = raw.split_at(1);
+    let (mark, rest) = match rest.strip_prefix('%') {
+        Some(weighted) => {
+            let end = weighted
+                .find('%')
+                .ok_or_else(|| "an answer weight is not closed".to_string())?;
+            let mark = weighted[..end]
+                .parse::<f32>()
+                .map_err(|_| format!("'{}' is not a valid weight", &weighted[..end]))?;
+            (mark.clamp(0.0, 100.0).round() as u8, &weighted[end + 1..])
+        }
+        None if prefix == "=" => (100, rest),
+        None => (0, rest),
+    };
+    let (answer, feedback) = match find_unescaped(rest, 0, "#") {
+        Some(pos) => (&rest[..pos], &rest[pos + 1..]),
+        None => (rest, ""),
+    };
+    Ok(AnswerOption {
+        is_question: true,
+        mark,
+        answer: unescape(answer.trim()),
+        feedback: unescape(feedback.trim()),
+    })
+}
+
+fn parse_gap(body: &str) -> Result<(ClozeType, Vec<AnswerOption>), String> {
+    // General feedback for the whole block is not kept.
+    let body = match find_unescaped(body, 0, "####") {
+        Some(pos) => &body[..pos],
+        None => body,
+    }
+    .trim();
+    if body.is_empty() {
+        return Err("essay questions are not supported".to_string());
+    }
+    let (kind, body) = match body.strip_prefix('#') {
+        Some(rest) => (ClozeType::Numerical, rest.trim()),
+        None => (ClozeType::ShortAnswer, body),
+    };
+    let head = body.split('#').next().unwrap_or_default().trim();
+    if matches!(head, "T" | "F" | "TRUE" | "FALSE") {
+        return Err("true/false questions are not supported".to_string());
+    }
+    if find_unescaped(body, 0, "->").is_some() {
+        return Err("matching questions are not supported".to_string());
+    }
+    // A numerical gap may give a single answer with no `=`.
+    if kind == ClozeType::Numerical && !body.starts_with('=') {
+        return Ok((kind, vec![parse_option(&format!("={}", body))?]));
+    }
+    if !body.starts_with('=') && !body.starts_with('~') {
+        return Err("answers must start with = or ~".to_string());
+    }
+
+    let mut options = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    let mut wrong_options = body.starts_with('~');
+    for (pos, c) in body.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if (c == '=' || c == '~') && pos > start {
+            options.push(parse_option(body[start..pos].trim())?);
+            start = pos;
+            wrong_options |= c == '~';
+        }
+    }
+    options.push(parse_option(body[start..].trim())?);
+    if wrong_options && kind == ClozeType::ShortAnswer {
+        Ok((ClozeType::MultiChoice, options))
+    } else {
+        Ok((kind, options))
+    }
+}
+
+fn parse_gift_question(text: &str) -> Result<Sentence, String> {
+    let mut text = text.trim();
+    let mut title = "";
+    if let Some(rest) = text.strip_prefix("::") {
+        let end = find_unescaped(rest, 0, "::").ok_or("the title is not closed")?;
+        title = &rest[..end];
+        text = rest[end + 2..].trim_start();
+    }
+    let mut html = false;
+    if text.starts_with('[') {
+        if let Some(end) = text.find(']') {
+            html = &text[..end + 1] == "[html]";
+            text = text[end + 1..].trim_start();
+        }
+    }
+
+    let mut parts: Vec<Part> = Vec::new();
+    let mut pos = 0;
+    while let Some(open) = find_unescaped(text, pos, "{") {
+        let close = find_unescaped(text, open, "}").ok_or("an answer block is not closed")?;
+        parts.push(Part::Text(unescape(&text[pos..open])));
+        let (kind, options) = parse_gap(&text[open + 1..close])?;
+        parts.push(Part::Gap(kind, options));
+        pos = close + 1;
+    }
+    parts.push(Part::Text(unescape(&text[pos..])));
+    if !parts.iter().any(|part| matches!(part, Part::Gap(..))) {
+        return Err(format!(
+            "'{}' has no answers (descriptions are not supported)",
+            title
+        ));
+    }
+    if html {
+        for part in parts.iter_mut() {
+            if let Part::Text(text) = part {
+                *text = strip_tags(text);
+            }
+        }
+    }
+
+    // Our own exports put the English on a line of its own, and other
+    // banks usually lead with it as a full sentence. Failing both, the
+    // question text with blanks in place of the gaps stands in for it.
+    let mut english = String::new();
+    if let Some(Part::Text(first)) = parts.first_mut() {
+        let split = match first.split_once('\n') {
+            Some((before, after)) => Some((before.to_string(), after.to_string())),
+            None => first
+                .rfind(['.', '?', '!'])
+                .map(|end| (first[..=end].to_string(), first[end + 1..].to_string())),
+        };
+        if let Some((before, after)) = split {
+            english = before.trim().to_string();
+            *first = after;
+        }
+    }
+    if english.is_empty() {
+        let shown: Vec<String> = parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.trim().to_string(),
+                Part::Gap(..) => "___".to_string(),
+            })
+            .filter(|text| !text.is_empty())
+            .collect();
+        english = shown.join(" ");
+    }
+
+    // The exporter adds the final full stop itself.
+    if let Some(Part::Text(last)) = parts.last_mut() {
+        let trimmed = last.trim_end();
+        *last = trimmed.strip_suffix('.').unwrap_or(trimmed).to_string();
+    }
+
+    let mut chunks: Vec<String> = Vec::new();
+    let mut answers: Vec<Vec<AnswerOption>> = Vec::new();
+    let mut kinds: Vec<Option<ClozeType>> = Vec::new();
+    let mut gap_count = 0;
+    for part in parts {
+        match part {
+            Part::Text(text) => {
+                let text = text.trim();
+                if !text.is_empty() {
+                    chunks.push(text.to_string());
+                    answers.push(vec![AnswerOption {
+                        is_question: false,
+                        mark: 0,
+                        answer: text.to_string(),
+                        feedback: "###Not a question###".to_string(),
+                    }]);
+                    kinds.push(None);
+                }
+            }
+            Part::Gap(kind, options) => {
+                // GIFT has no English for a gap, and its answer would
+                // give itself away wherever the chunk is shown.
+                gap_count += 1;
+                chunks.push(format!("gap {}", gap_count));
+                answers.push(options);
+                // Plain short answers follow the bank default.
+                kinds.push(Some(kind).filter(|kind| *kind != ClozeType::ShortAnswer));
+            }
+        }
+    }
+    let mut sentence = Sentence::with_chunks(english, chunks, answers);
+    for (settings, kind) in sentence.chunk_settings.iter_mut().zip(kinds) {
+        settings.gap_type = kind;
+    }
+    sentence.completed = true;
+    Ok(sentence)
+}
+
+// Returns the questions that could be read and a note for each one
+// that could not.
+pub fn parse_gift(contents: &str) -> (Vec<Sentence>, Vec<String>) {
+    let mut questions = Vec::new();
+    let mut problems = Vec::new();
+    let mut block: Vec<&str> = Vec::new();
+    let mut number = 0;
+    for line in contents.lines().chain(std::iter::once("")) {
+        let trimmed = line.trim();
+        if trimmed.starts_with("//") || trimmed.starts_with("$CATEGORY:") {
+            continue;
+        }
+        if !trimmed.is_empty() {
+            block.push(trimmed);
+            continue;
+        }
+        if block.is_empty() {
+            continue;
+        }
+        number += 1;
+        match parse_gift_question(&block.join(" ")) {
+            Ok(sentence) => questions.push(sentence),
+            Err(problem) => problems.push(format!("Question {}: {}", number, problem)),
+        }
+        block.clear();
+    }
+    (questions, problems)
+}
+
+pub fn import_gift_file(bank: &mut Bank) {
+    print!("Which file? ");
+    let path = read_input();
+    let contents = match fs::read_to_string(path.trim()) {
+        Ok(contents) => contents,
+        Err(error) => {
+            print_boxed(&format!("Unable to read {}: {}\nContinue: c", path, error));
+            read_input();
+            return;
+        }
+    };
+    let (questions, problems) = parse_gift(&contents);
+    let mut report = format!("Imported {} questions.", questions.len());
+    if !problems.is_empty() {
+        report.push_str(&format!("\n{} questions were skipped:", problems.len()));
+        for problem in problems {
+            report.push_str(&format!("\n  {}", problem));
+        }
+    }
+    for question in questions {
+        bank.add(question);
+    }
+    print_boxed(&format!("{}\nContinue: c", report));
+    read_input();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_english_and_a_gap() {
+        let (questions, problems) =
+            parse_gift("::Q1::The men are here\\nοἱ ἄνδρες {=πάρεισι#Good ~%50%πάρεστι}.\n");
+        assert!(problems.is_empty());
+        let sentence = &questions[0];
+        assert_eq!(sentence.initial_sentence, "The men are here");
+        assert_eq!(sentence.splits[0], ["οἱ ἄνδρες", "gap 1"]);
+        assert!(!sentence.answers[0][0].is_question);
+        let gap = &sentence.answers[1];
+        assert_eq!(gap[0].feedback, "Good");
+        assert_eq!(gap[1].mark, 50);
+        assert_eq!(
+            sentence.chunk_settings[1].gap_type,
+            Some(ClozeType::MultiChoice)
+        );
+    }
+
+    #[test]
+    fn short_answers_follow_the_bank_default() {
+        let (questions, _) = parse_gift("Two. {=δύο =duo}\n");
+        assert_eq!(questions[0].initial_sentence, "Two.");
+        assert_eq!(questions[0].chunk_settings[0].gap_type, None);
+        assert_eq!(questions[0].answers[0].len(), 2);
+    }
+
+    #[test]
+    fn reports_what_it_cannot_read() {
+        let contents =
+            "// a comment\n$CATEGORY: top\n\nIs it true? {T}\n\nFine. {=a}\n\nEssay. {}\n";
+        let (questions, problems) = parse_gift(contents);
+        assert_eq!(questions.len(), 1);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].starts_with("Question 1:"));
+        assert!(problems[1].starts_with("Question 3:"));
+    }
+}
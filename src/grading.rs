@@ -0,0 +1,407 @@
+use crate::catchall::exported_answers;
+use crate::cloze::ClozeType;
+use crate::{AnswerOption, Bank, Sentence};
+
+// A pattern as characters to match, with `None` for each `*`.
+fn pattern_tokens(pattern: &str, case_sensitive: bool) -> Vec<Option<char>> {
+    let pattern = if case_sensitive {
+        pattern.to_string()
+    } else {
+        pattern.to_lowercase()
+    };
+    let mut tokens = Vec::new();
+    let mut chars = pattern.trim().chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => tokens.push(Some(chars.next().unwrap_or('\\'))),
+            '*' => tokens.push(None),
+            _ => tokens.push(Some(c)),
+        }
+    }
+    tokens
+}
+
+pub fn is_wildcard(pattern: &str) -> bool {
+    pattern_tokens(pattern, true).contains(&None)
+}
+
+// Whether `general` matches all of `specific`. A `*` in `specific` can
+// only be taken up by a `*` in `general`, so given a plain response
+// this is ordinary matching.
+fn covers(general: &[Option<char>], specific: &[Option<char>]) -> bool {
+    // matched[j] is true when the tokens so far can produce the first j
+    // tokens of `specific`.
+    let mut matched = vec![false; specific.len() + 1];
+    matched[0] = true;
+    for token in general {
+        let mut next = vec![false; specific.len() + 1];
+        for j in 0..=specific.len() {
+            next[j] = match token {
+                None => matched[j] || (j > 0 && next[j - 1]),
+                Some(_) => j > 0 && matched[j - 1] && specific[j - 1] == *token,
+            };
+        }
+        matched = next;
+    }
+    matched[specific.len()]
+}
+
+// Moodle's short answer matching: the whole response must match, `*`
+// stands for any run of characters and `\*` for a literal star.
+pub fn wildcard_match(pattern: &str, response: &str, case_sensitive: bool) -> bool {
+    let response = if case_sensitive {
+        response.to_string()
+    } else {
+        response.to_lowercase()
+    };
+    let response: Vec<Option<char>> = response.trim().chars().map(Some).collect();
+    covers(&pattern_tokens(pattern, case_sensitive), &response)
+}
+
+// Numerical answers are written `value` or `value:tolerance`; `*`
+// accepts any number.
+fn numerical_range(answer: &str) -> Option<(f64, f64)> {
+    if answer.trim() == "*" {
+        return Some((f64::NEG_INFINITY, f64::INFINITY));
+    }
+    let (value, tolerance) = match answer.split_once(':') {
+        Some((value, tolerance)) => (value, tolerance.trim().parse::<f64>().unwrap_or(0.0)),
+        None => (answer, 0.0),
+    };
+    let value = value.trim().parse::<f64>().ok()?;
+    Some((value - tolerance.abs(), value + tolerance.abs()))
+}
+
+fn numerical_match(answer: &str, response: &str) -> bool {
+    match (numerical_range(answer), response.trim().parse::<f64>()) {
+        (Some((low, high)), Ok(response)) => low <= response && response <= high,
+        _ => false,
+    }
+}
+
+// As in Moodle the options are tried in order and the first that
+// matches wins. Multiple choice responses may give the option number.
+pub fn match_option<'a>(
+    options: &'a [AnswerOption],
+    kind: ClozeType,
+    response: &str,
+) -> Option<&'a AnswerOption> {
+    if kind.is_multichoice() {
+        if let Ok(num) = response.trim().parse::<usize>() {
+            return options.get(num.wrapping_sub(1));
+        }
+        return options
+            .iter()
+            .find(|option| option.answer.trim() == response.trim());
+    }
+    options.iter().find(|option| match kind {
+        ClozeType::Numerical => numerical_match(&option.answer, response),
+        ClozeType::ShortAnswerCase => wildcard_match(&option.answer, response, true),
+        _ => wildcard_match(&option.answer, response, false),
+    })
+}
+
+pub struct GapGrade {
+    pub gap: usize,
+    pub option: Option<AnswerOption>,
+    pub fraction: f32,
+    pub feedback: String,
+}
+
+pub struct QuestionGrade {
+    pub gaps: Vec<GapGrade>,
+    pub fraction: f32,
+}
+
+// The chunks that become subquestions, in order.
+pub fn gaps(sentence: &Sentence) -> Vec<usize> {
+    (0..sentence.answers.len())
+        .filter(|idx| {
+            sentence.answers[*idx]
+                .first()
+                .is_some_and(|a| a.is_question)
+        })
+        .collect()
+}
+
+// Finds a gap again by its chunk's text, since joining or reordering
+// chunks moves it. The stored index settles chunks with the same text.
+pub fn locate_gap(sentence: &Sentence, gap: usize, chunk: &str) -> Option<usize> {
+    let chunks = &sentence.splits[sentence.current_split];
+    let matching: Vec<usize> = gaps(sentence)
+        .into_iter()
+        .filter(|idx| chunks.get(*idx).is_some_and(|text| text == chunk))
+        .collect();
+    if matching.contains(&gap) {
+        Some(gap)
+    } else {
+        matching.first().copied()
+    }
+}
+
+// Grades against the answers as they are exported, catch-all and all,
+// so that what is checked here is what Moodle will do. A blank response
+// is left unanswered, so it scores nothing and gets no feedback even
+// where there is a `*` option.
+pub fn grade_gap(bank: &Bank, sentence: &Sentence, gap: usize, response: &str) -> GapGrade {
+    let kind = sentence.gap_type(gap, bank.default_gap_type);
+    let options = exported_answers(bank, sentence, gap);
+    let option = match response.trim() {
+        "" => None,
+        _ => match_option(&options, kind, response).cloned(),
+    };
+    GapGrade {
+        gap,
+        fraction: option
+            .as_ref()
+            .map_or(0.0, |option| option.mark as f32 / 100.0),
+        feedback: option
+            .as_ref()
+            .map_or(String::new(), |option| option.feedback.clone()),
+        option,
+    }
+}
+
+// Takes one response per gap and, like Moodle's cloze questions, scores
+// the question as the weighted mean of the gap fractions. Missing
+// responses count as blank.
+pub fn grade_question(bank: &Bank, sentence: &Sentence, responses: &[&str]) -> QuestionGrade {
+    let gaps: Vec<GapGrade> = gaps(sentence)
+        .into_iter()
+        .enumerate()
+        .map(|(num, gap)| grade_gap(bank, sentence, gap, responses.get(num).unwrap_or(&"")))
+        .collect();
+    let scored: f32 = gaps
+        .iter()
+        .map(|grade| grade.fraction * sentence.gap_weight(grade.gap) as f32)
+        .sum();
+    let total = sentence.total_weight();
+    QuestionGrade {
+        gaps,
+        fraction: if total == 0 {
+            0.0
+        } else {
+            scored / total as f32
+        },
+    }
+}
+
+// Whether every response `later` accepts is already taken by `earlier`.
+// Multiple choice options are picked rather than matched, so none is
+// ever hidden.
+fn subsumes(earlier: &AnswerOption, later: &AnswerOption, kind: ClozeType) -> bool {
+    match kind {
+        _ if kind.is_multichoice() => false,
+        ClozeType::Numerical => match (
+            numerical_range(&earlier.answer),
+            numerical_range(&later.answer),
+        ) {
+            (Some((low, high)), Some((from, to))) => low <= from && to <= high,
+            _ => false,
+        },
+        _ => {
+            let case_sensitive = kind == ClozeType::ShortAnswerCase;
+            covers(
+                &pattern_tokens(&earlier.answer, case_sensitive),
+                &pattern_tokens(&later.answer, case_sensitive),
+            )
+        }
+    }
+}
+
+// Pairs of an option that can never match and the first option before
+// it that takes all its responses.
+pub fn shadowed(options: &[AnswerOption], kind: ClozeType) -> Vec<(usize, usize)> {
+    (0..options.len())
+        .filter_map(|later| {
+            (0..later)
+                .find(|earlier| subsumes(&options[*earlier], &options[later], kind))
+                .map(|earlier| (later, earlier))
+        })
+        .collect()
+}
+
+// An order for the options in which nothing comes after a broader
+// pattern that would hide it, otherwise keeping the author's order.
+// Options that accept exactly the same responses stay as they are.
+pub fn specific_first(options: &[AnswerOption], kind: ClozeType) -> Vec<usize> {
+    let broader = |a: usize, b: usize| {
+        subsumes(&options[a], &options[b], kind) && !subsumes(&options[b], &options[a], kind)
+    };
+    let mut remaining: Vec<usize> = (0..options.len()).collect();
+    let mut order = Vec::new();
+    while !remaining.is_empty() {
+        let pos = remaining
+            .iter()
+            .position(|a| !remaining.iter().any(|b| broader(*a, *b)))
+            .unwrap_or(0);
+        order.push(remaining.remove(pos));
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChunkSettings;
+
+    fn option(answer: &str, mark: u8, feedback: &str) -> AnswerOption {
+        AnswerOption {
+            is_question: true,
+            mark,
+            answer: answer.to_string(),
+            feedback: feedback.to_string(),
+        }
+    }
+
+    fn text(chunk: &str) -> Vec<AnswerOption> {
+        vec![AnswerOption {
+            is_question: false,
+            mark: 0,
+            answer: chunk.to_string(),
+            feedback: "###Not a question###".to_string(),
+        }]
+    }
+
+    // A question with one gap, "οἱ ἄνδρες", before the chunk "πάρεισι".
+    fn question(options: Vec<AnswerOption>, kind: Option<ClozeType>) -> Sentence {
+        let mut sentence = Sentence::with_chunks(
+            "The men are here".to_string(),
+            vec!["οἱ ἄνδρες".to_string(), "πάρεισι".to_string()],
+            vec![options, text("πάρεισι")],
+        );
+        sentence.chunk_settings[0].gap_type = kind;
+        sentence
+    }
+
+    fn tokens(pattern: &str) -> Vec<Option<char>> {
+        pattern_tokens(pattern, true)
+    }
+
+    #[test]
+    fn first_matching_option_wins() {
+        let options = vec![
+            option("οἱ ἄνδρες", 100, "Well done!"),
+            option("οἱ *", 50, "Check the noun."),
+            option("*", 0, "Try again!"),
+        ];
+        let kind = ClozeType::ShortAnswer;
+        let found = |response| match_option(&options, kind, response).map(|o| o.mark);
+        assert_eq!(found("οἱ ἄνδρες"), Some(100));
+        assert_eq!(found("οἱ γυναῖκες"), Some(50));
+        assert_eq!(found("ἄνδρες"), Some(0));
+
+        let reversed: Vec<AnswerOption> = options.iter().rev().cloned().collect();
+        let first = match_option(&reversed, kind, "οἱ ἄνδρες").unwrap();
+        assert_eq!(first.answer, "*");
+    }
+
+    #[test]
+    fn star_matches_any_run_and_escaped_star_itself() {
+        assert!(wildcard_match("*", "", false));
+        assert!(wildcard_match("a*c", "ac", false));
+        assert!(wildcard_match("a*c", "abbbc", false));
+        assert!(!wildcard_match("a*c", "abcd", false));
+        assert!(wildcard_match("a\\*c", "a*c", false));
+        assert!(!wildcard_match("a\\*c", "abc", false));
+        assert!(is_wildcard("a*"));
+        assert!(!is_wildcard("a\\*"));
+    }
+
+    #[test]
+    fn only_shortanswer_c_is_case_sensitive() {
+        let options = vec![option("Σωκράτης", 100, "")];
+        let found = |kind| match_option(&options, kind, "σωκράτης").is_some();
+        assert!(found(ClozeType::ShortAnswer));
+        assert!(!found(ClozeType::ShortAnswerCase));
+        assert!(match_option(&options, ClozeType::ShortAnswerCase, "Σωκράτης").is_some());
+    }
+
+    #[test]
+    fn responses_are_trimmed() {
+        assert!(wildcard_match("λόγος", "  λόγος ", false));
+    }
+
+    #[test]
+    fn multichoice_takes_the_option_number_or_text() {
+        let options = vec![option("ὁ", 0, ""), option("οἱ", 100, "")];
+        let kind = ClozeType::MultiChoice;
+        assert_eq!(match_option(&options, kind, "2").unwrap().answer, "οἱ");
+        assert_eq!(match_option(&options, kind, "ὁ").unwrap().mark, 0);
+        assert!(match_option(&options, kind, "3").is_none());
+        assert!(match_option(&options, kind, "0").is_none());
+    }
+
+    #[test]
+    fn numerical_answers_take_a_tolerance() {
+        assert_eq!(numerical_range("5"), Some((5.0, 5.0)));
+        assert_eq!(numerical_range("5:0.5"), Some((4.5, 5.5)));
+        assert_eq!(numerical_range("5:-1"), Some((4.0, 6.0)));
+        assert_eq!(
+            numerical_range("*"),
+            Some((f64::NEG_INFINITY, f64::INFINITY))
+        );
+        assert_eq!(numerical_range("five"), None);
+        assert!(numerical_match("5:0.5", "5.4"));
+        assert!(!numerical_match("5:0.5", "5.6"));
+        assert!(!numerical_match("5", "five"));
+    }
+
+    #[test]
+    fn covers_compares_patterns() {
+        assert!(covers(&tokens("*"), &tokens("abc")));
+        assert!(covers(&tokens("a*"), &tokens("ab*")));
+        assert!(!covers(&tokens("ab*"), &tokens("a*")));
+        assert!(covers(&tokens("a*c"), &tokens("a*b*c")));
+        assert!(!covers(&tokens("abc"), &tokens("a*c")));
+        assert!(covers(&tokens("abc"), &tokens("abc")));
+    }
+
+    #[test]
+    fn blank_responses_score_nothing_even_against_a_star() {
+        let bank = Bank::new();
+        let sentence = question(vec![option("*", 100, "Anything goes.")], None);
+        let grade = grade_gap(&bank, &sentence, 0, "  ");
+        assert!(grade.option.is_none());
+        assert_eq!(grade.fraction, 0.0);
+        assert_eq!(grade.feedback, "");
+        assert_eq!(grade_gap(&bank, &sentence, 0, "x").fraction, 1.0);
+    }
+
+    #[test]
+    fn unmatched_responses_get_the_catch_all() {
+        let mut bank = Bank::new();
+        let sentence = question(vec![option("οἱ ἄνδρες", 100, "")], None);
+        assert!(grade_gap(&bank, &sentence, 0, "ὁ ἀνήρ").option.is_none());
+        bank.catch_all.enabled = true;
+        let grade = grade_gap(&bank, &sentence, 0, "ὁ ἀνήρ");
+        assert_eq!(grade.option.unwrap().answer, "*");
+        assert_eq!(grade.feedback, bank.catch_all.feedback);
+    }
+
+    #[test]
+    fn questions_score_the_weighted_mean_of_their_gaps() {
+        let bank = Bank::new();
+        let mut sentence = Sentence::with_chunks(
+            "The man is here".to_string(),
+            vec!["ὁ ἀνὴρ".to_string(), "πάρεστι".to_string()],
+            vec![
+                vec![option("ὁ ἀνὴρ", 100, "")],
+                vec![option("πάρεστι", 100, ""), option("πάρεισι", 50, "")],
+            ],
+        );
+        sentence.chunk_settings = vec![
+            ChunkSettings::default(),
+            ChunkSettings {
+                weight: Some(3),
+                ..ChunkSettings::default()
+            },
+        ];
+        // Two words weigh 2 and the explicit weight 3.
+        let grade = grade_question(&bank, &sentence, &["ὁ ἀνὴρ", "πάρεισι"]);
+        assert_eq!(grade.gaps.len(), 2);
+        assert!((grade.fraction - (2.0 + 1.5) / 5.0).abs() < 1e-6);
+        let missing = grade_question(&bank, &sentence, &["ὁ ἀνὴρ"]);
+        assert!((missing.fraction - 2.0 / 5.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::cloze::highlight_cloze;
+use crate::config::config;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::{Hinter, HistoryHinter};
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INPUT_CLOSED: AtomicBool = AtomicBool::new(false);
+
+// Suggests whole-line completions from a list chosen by whoever asked
+// for the input, falling back to history for hints.
+pub struct InputHelper {
+    completions: Vec<String>,
+    remembered: HashMap<String, Vec<String>>,
+    hinter: HistoryHinter,
+    cloze: bool,
+}
+
+impl InputHelper {
+    fn matches(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_lowercase();
+        self.completions
+            .iter()
+            .filter(|candidate| candidate.to_lowercase().starts_with(&prefix))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Completer for InputHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok((0, self.matches(&line[..pos])))
+    }
+}
+
+impl Hinter for InputHelper {
+    fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<String> {
+        if line.is_empty() || pos < line.len() {
+            return None;
+        }
+        self.completions
+            .iter()
+            .find(|candidate| candidate.starts_with(line) && candidate.len() > line.len())
+            .map(|candidate| candidate[line.len()..].to_string())
+            .or_else(|| self.hinter.hint(line, pos, ctx))
+    }
+}
+
+impl Highlighter for InputHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if self.cloze && config().color {
+            Cow::Owned(highlight_cloze(line))
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+        self.cloze
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        if !config().color {
+            return Cow::Borrowed(hint);
+        }
+        Cow::Owned(format!("\x1b[2m{}\x1b[0m", hint))
+    }
+}
+
+impl Validator for InputHelper {}
+
+impl Helper for InputHelper {}
+
+thread_local! {
+    static EDITOR: RefCell<Editor<InputHelper>> = RefCell::new(new_editor());
+}
+
+fn history_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".string_tweaker_history"))
+}
+
+fn new_editor() -> Editor<InputHelper> {
+    let mut rl = Editor::<InputHelper>::new();
+    rl.set_helper(Some(InputHelper {
+        completions: Vec::new(),
+        remembered: HashMap::new(),
+        hinter: HistoryHinter {},
+        cloze: false,
+    }));
+    if let Some(path) = history_path() {
+        // There is no history yet on first use.
+        let _ = rl.load_history(&path);
+    }
+    rl
+}
+
+fn read_line(prompt: &str, initial: &str, completions: Vec<String>) -> String {
+    read_line_as(prompt, initial, completions, false)
+}
+
+fn read_line_as(prompt: &str, initial: &str, completions: Vec<String>, cloze: bool) -> String {
+    EDITOR.with(|cell| {
+        let mut rl = cell.borrow_mut();
+        if let Some(helper) = rl.helper_mut() {
+            helper.completions = completions;
+            helper.cloze = cloze;
+        }
+        let readline = if initial.is_empty() {
+            rl.readline(prompt)
+        } else {
+            rl.readline_with_initial(prompt, (initial, ""))
+        };
+        // Piped input would otherwise spin on an empty line forever, so
+        // its end is passed on for the screens to quit and save.
+        if let Err(ReadlineError::Eof) = readline {
+            if !io::stdin().is_terminal() {
+                INPUT_CLOSED.store(true, Ordering::Relaxed);
+            }
+        }
+        let line = readline
+            .map(|line| line.trim_end_matches(&['\r', '\n'][..]).to_string())
+            .unwrap_or_default();
+        // Menu keys and numbers would only crowd out the answers.
+        if line.chars().count() > 1 && !line.chars().all(|c| c.is_ascii_digit()) {
+            rl.add_history_entry(line.as_str());
+            if let Some(path) = history_path() {
+                let _ = rl.save_history(&path);
+            }
+        }
+        line
+    })
+}
+
+pub fn read_input() -> String {
+    read_line(">> ", "", Vec::new())
+}
+
+pub fn read_input_with(completions: Vec<String>) -> String {
+    read_line(">> ", "", completions)
+}
+
+pub fn read_input_with_initial(initial: &str, completions: Vec<String>) -> String {
+    read_line(">> ", initial, completions)
+}
+
+pub fn read_cloze_code(initial: &str) -> String {
+    read_line_as(">> ", initial, Vec::new(), true)
+}
+
+pub fn input_closed() -> bool {
+    INPUT_CLOSED.load(Ordering::Relaxed)
+}
+
+// Once the input has closed this gives a number too high for anything.
+pub fn get_num_choice(prompt: &str) -> usize {
+    loop {
+        let choice = read_line(prompt, "", Vec::new());
+        if input_closed() {
+            return usize::MAX;
+        }
+        let idx = choice.trim().parse::<usize>();
+        match idx {
+            Ok(num) if num > 0 => return num - 1,
+            _ => {
+                println!("You must enter a number.");
+                continue;
+            }
+        }
+    }
+}
+
+pub fn remember_answer(chunk: &str, answer: &str) {
+    EDITOR.with(|cell| {
+        if let Some(helper) = cell.borrow_mut().helper_mut() {
+            let forms = helper.remembered.entry(chunk.to_lowercase()).or_default();
+            if !forms.iter().any(|form| form == answer) {
+                forms.push(answer.to_string());
+            }
+        }
+    })
+}
+
+pub fn remembered_answers(chunk: &str) -> Vec<String> {
+    EDITOR.with(|cell| {
+        cell.borrow()
+            .helper()
+            .and_then(|helper| helper.remembered.get(&chunk.to_lowercase()).cloned())
+            .unwrap_or_default()
+    })
+}
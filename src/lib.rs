@@ -1,19 +1,164 @@
+mod catchall;
+mod changes;
+mod cloze;
+mod config;
+mod gift;
+pub mod grading;
+mod input;
+mod lint;
+mod metadata;
+mod practice;
 mod printing;
-use printing::{
-    move_print_questions, print_answer_options, print_boxed, print_enumerated,
-    print_enumerated_answers, print_line, print_questions, print_to_file,
+mod punctuation;
+mod responses;
+mod screens;
+mod stats;
+mod storage;
+mod template;
+mod testcases;
+mod theme;
+mod tui;
+use catchall::CatchAll;
+pub use cloze::ClozeType;
+use cloze::{moodle_subquestion, parse_cloze};
+use config::config;
+use grading::is_wildcard;
+use input::{
+    get_num_choice, input_closed, read_cloze_code, read_input, read_input_with,
+    read_input_with_initial, remember_answer, remembered_answers,
 };
+use metadata::Metadata;
+use printing::{ask_export_details, print_boxed, print_key_help, print_line};
+use punctuation::{end_mark, is_punctuation, join_parts, tokenise};
+use responses::CommonResponse;
+use screens::run_screens;
+use serde::{Deserialize, Serialize};
+use stats::QuestionStats;
 use std::error::Error;
+use std::mem;
+pub use storage::load_bank;
+use storage::save_bank;
+use tui::run_tui;
 
+#[derive(Serialize, Deserialize)]
 pub struct Sentence {
+    pub id: u32,
     pub initial_sentence: String,
     pub splits: Vec<Vec<String>>,
     pub current_split: usize,
     pub answers: Vec<Vec<AnswerOption>>,
+    pub chunk_settings: Vec<ChunkSettings>,
+    pub metadata: Metadata,
+    pub exercise: Option<usize>,
+    #[serde(default)]
+    pub exported_hash: Option<u64>,
+    #[serde(default)]
+    pub gift_hash: Option<u64>,
+    // One for each split before the current one.
+    #[serde(default)]
+    pub answer_history: Vec<SavedAnswers>,
+    #[serde(default)]
+    pub test_cases: Vec<TestCase>,
     pub completed: bool,
 }
 
-#[derive(Debug)]
+// Per-chunk settings kept alongside `answers`; `None` falls back to
+// the bank-wide default.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ChunkSettings {
+    pub gap_type: Option<ClozeType>,
+    pub weight: Option<u32>,
+    pub category: Option<Category>,
+}
+
+// The answers and chunk settings a split had when the next one was made,
+// so that reverting brings back the answers a join set aside.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SavedAnswers {
+    pub answers: Vec<Vec<AnswerOption>>,
+    pub chunk_settings: Vec<ChunkSettings>,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Category {
+    Noun,
+    Verb,
+    Adjective,
+    Participle,
+}
+
+impl Category {
+    pub const ALL: [Category; 4] = [
+        Category::Noun,
+        Category::Verb,
+        Category::Adjective,
+        Category::Participle,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Noun => "noun",
+            Category::Verb => "verb",
+            Category::Adjective => "adjective",
+            Category::Participle => "participle",
+        }
+    }
+
+    fn weight(self) -> u32 {
+        match self {
+            Category::Noun | Category::Adjective => 1,
+            Category::Verb => 2,
+            Category::Participle => 3,
+        }
+    }
+}
+
+// A student response for every gap, in order, with the mark and
+// feedback it should get.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TestCase {
+    pub gaps: Vec<ExpectedGrade>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExpectedGrade {
+    pub response: String,
+    pub mark: u8,
+    pub feedback: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Exercise {
+    pub stage: String,
+    pub name: String,
+}
+
+// `last_id` only ever grows, so a deleted question's ID is never
+// handed to a new one.
+#[derive(Serialize, Deserialize)]
+pub struct Bank {
+    pub questions: Vec<Sentence>,
+    pub last_id: u32,
+    pub exercises: Vec<Exercise>,
+    pub default_gap_type: ClozeType,
+    pub defaults: Metadata,
+    #[serde(default)]
+    pub catch_all: CatchAll,
+    #[serde(default)]
+    pub common_responses: Vec<CommonResponse>,
+    #[serde(default)]
+    pub stats: Vec<QuestionStats>,
+}
+
+const FEEDBACK_LIBRARY: [&str; 5] = [
+    "Try again!",
+    "Well done!",
+    "Look at your notes on nouns.",
+    "Look at your notes on verbs.",
+    "Look at your notes on adjectives.",
+];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AnswerOption {
     pub is_question: bool,
     pub mark: u8,
@@ -24,202 +169,281 @@ pub struct AnswerOption {
 impl Sentence {
     fn new(entry: String) -> Self {
         let mut splits: Vec<Vec<String>> = Vec::new();
-        let mut words: Vec<String> = Vec::new();
         let answers: Vec<Vec<AnswerOption>> = Vec::new();
-        for word in entry.split_whitespace() {
-            words.push(String::from(word));
-        }
-        splits.push(words);
+        splits.push(tokenise(&entry));
         Self {
+            id: 0,
             initial_sentence: entry,
             splits,
             current_split: 0,
             answers,
+            chunk_settings: Vec::new(),
+            metadata: Metadata::default(),
+            exercise: None,
+            exported_hash: None,
+            gift_hash: None,
+            answer_history: Vec::new(),
+            test_cases: Vec::new(),
             completed: false,
         }
     }
-}
 
-pub fn run() -> Result<(), Box<dyn Error>> {
-    let mut questions: Vec<Sentence> = Vec::new();
+    fn with_chunks(entry: String, chunks: Vec<String>, answers: Vec<Vec<AnswerOption>>) -> Self {
+        let mut sentence = Self::new(entry);
+        sentence.splits = vec![chunks];
+        sentence.chunk_settings = vec![ChunkSettings::default(); answers.len()];
+        sentence.answers = answers;
+        sentence
+    }
 
-    loop {
-        // clear the screen
-        print!("\x1B[2J");
-        println!("");
-        print_boxed("Sentences into Greek");
-        println!(" ~i: incomplete ~D: done");
-        print_questions(&questions);
-        print_boxed(&format!(
-            "{:20}{:20}{:20}{:16}\n{:20}{:20}{:20}",
-            "Add question: a",
-            "Edit question: e",
-            "Delete question: d",
-            "Move question: m",
-            "Print to file: p",
-            "Start again: s",
-            "Quit: q"
-        ));
-        match read_input().as_str() {
-            "a" => enter_question(&mut questions),
-            "e" => {
-                let choice = get_num_choice("Enter no.: ");
-                let chosen_question = &mut questions[choice];
-                process_question(chosen_question);
-            }
-            "d" => {
-                let choice = get_num_choice("Enter no.: ");
-                questions.remove(choice);
-            }
-            "m" => {
-                let choice = get_num_choice("Enter no.: ");
-                move_question_dialog(&mut questions, choice);
-            }
-            "p" => print_to_file(&questions),
-            "s" => {
-                questions.clear();
-                continue;
-            }
-            "q" => break,
-            _ => continue,
+    pub fn gap_type(&self, idx: usize, default: ClozeType) -> ClozeType {
+        self.chunk_settings
+            .get(idx)
+            .and_then(|settings| settings.gap_type)
+            .unwrap_or(default)
+    }
+
+    // An explicit weight wins, then the chunk's grammar category, and
+    // otherwise each word in the chunk counts once.
+    pub fn gap_weight(&self, idx: usize) -> u32 {
+        let settings = self.chunk_settings.get(idx);
+        if let Some(weight) = settings.and_then(|settings| settings.weight) {
+            return weight;
+        }
+        if let Some(category) = settings.and_then(|settings| settings.category) {
+            return category.weight();
         }
+        self.splits[self.current_split]
+            .get(idx)
+            .map_or(1, |chunk| chunk.split_whitespace().count().max(1) as u32)
     }
-    Ok(())
-}
 
-fn move_question_dialog(bank: &mut Vec<Sentence>, mut idx: usize) {
-    loop {
-        // clear the screen
-        print!("\x1B[2J");
-        print_boxed("Move question");
-        move_print_questions(bank, idx);
-        print_boxed("Move up: u    Move down: d    Accept: a");
-        match read_input().as_str() {
-            "u" => {
-                bank[idx - 1..idx + 1].rotate_right(1);
-                idx -= 1;
-            }
-            "d" => {
-                bank[idx..idx + 2].rotate_left(1);
-                idx += 1;
-            }
-            "a" => break,
-            _ => continue,
+    // The first full-mark answer of each gap with the fixed text in
+    // between, unless the author has written their own.
+    pub fn model_answer(&self) -> String {
+        if let Some(model) = &self.metadata.model_answer {
+            return model.clone();
+        }
+        if self.answers.is_empty() {
+            return String::new();
         }
+        let mut parts: Vec<String> = Vec::new();
+        for group in self.answers.iter() {
+            let part = match group.first() {
+                Some(first) if !first.is_question => &first.answer,
+                Some(_) => match group.iter().find(|answer| answer.mark == 100) {
+                    Some(answer) => &answer.answer,
+                    None => "___",
+                },
+                None => "___",
+            };
+            parts.push(part.to_string());
+        }
+        let end = end_mark(&parts, &config().language);
+        format!("{}{}", join_parts(&parts), end)
+    }
+
+    pub fn total_weight(&self) -> u32 {
+        (0..self.answers.len())
+            .filter(|idx| self.answers[*idx].first().is_some_and(|a| a.is_question))
+            .map(|idx| self.gap_weight(idx))
+            .sum()
     }
 }
 
-fn enter_question(bank: &mut Vec<Sentence>) {
-    let mut sentence: Sentence = get_sentence();
-    print_boxed("Return to menu: a    Process this one: p");
-    match read_input().as_str() {
-        "a" => bank.push(sentence),
-        "p" => {
-            process_question(&mut sentence);
-            bank.push(sentence);
+impl Bank {
+    pub fn add(&mut self, mut sentence: Sentence) {
+        self.last_id += 1;
+        sentence.id = self.last_id;
+        self.questions.push(sentence);
+    }
+
+    fn new() -> Self {
+        Self {
+            questions: Vec::new(),
+            last_id: 1000,
+            exercises: Vec::new(),
+            default_gap_type: ClozeType::ShortAnswer,
+            defaults: Metadata::default(),
+            catch_all: CatchAll::default(),
+            common_responses: Vec::new(),
+            stats: Vec::new(),
         }
-        _ => {}
     }
 }
 
-fn process_question(sentence: &mut Sentence) {
-    edit_sentence(sentence);
-    set_answers(sentence);
+pub fn run() -> Result<(), Box<dyn Error>> {
+    let mut bank = load_bank(&config().bank_path)?;
+    if config().tui {
+        run_tui(&mut bank)?;
+    } else {
+        run_screens(&mut bank);
+    }
+    save_bank(&bank, &config().bank_path)?;
+    Ok(())
 }
 
-fn read_input() -> String {
-    let mut rl = rustyline::Editor::<()>::new();
-    let readline = rl.readline(">> ");
-    readline.unwrap_or_default()
+fn add_exercise(bank: &mut Bank) {
+    let (stage, name) = ask_export_details();
+    bank.exercises.push(Exercise { stage, name });
 }
 
-fn get_num_choice(prompt: &str) -> usize {
-    loop {
-        let mut rl = rustyline::Editor::<()>::new();
-        let readline = rl.readline(prompt);
-        let choice = readline.unwrap_or_default();
-        let idx = choice.parse::<usize>();
-        match idx {
-            Ok(num) => return num - 1,
-            Err(_error) => {
-                println!("You must enter a number.");
-                continue;
+// Questions in a deleted exercise are left without one.
+fn delete_exercise(bank: &mut Bank, idx: usize) {
+    bank.exercises.remove(idx);
+    for question in bank.questions.iter_mut() {
+        question.exercise = match question.exercise {
+            Some(current) if current == idx => None,
+            Some(current) if current > idx => Some(current - 1),
+            other => other,
+        };
+    }
+}
+
+// Reads question numbers such as `1,3,5-8`, ignoring any out of range.
+fn parse_selection(input: &str, len: usize) -> Vec<usize> {
+    let mut selected = Vec::new();
+    for item in input.split(',').map(str::trim) {
+        let (first, last) = match item.split_once('-') {
+            Some((first, last)) => (first.trim().parse::<usize>(), last.trim().parse::<usize>()),
+            None => (item.parse::<usize>(), item.parse::<usize>()),
+        };
+        if let (Ok(first), Ok(last)) = (first, last) {
+            for num in first.max(1)..=last.min(len) {
+                if !selected.contains(&(num - 1)) {
+                    selected.push(num - 1);
+                }
             }
         }
     }
+    selected
 }
 
-fn revert_splits(sentence: &mut Sentence) {
-    if sentence.current_split > 0 {
-        sentence.current_split -= 1;
-        sentence.splits.pop();
+fn assign_exercise(bank: &mut Bank, idx: usize) -> usize {
+    print!("Which questions (e.g. 1,3,5-8)? ");
+    let selected = parse_selection(&read_input(), bank.questions.len());
+    for question in selected.iter() {
+        bank.questions[*question].exercise = Some(idx);
     }
+    selected.len()
 }
 
-fn get_sentence() -> Sentence {
-    loop {
-        print_boxed("Please enter your sentence.");
-        println!("");
-        let sentence = Sentence::new(read_input());
-        println!("You entered: {}", sentence.initial_sentence);
-        println!("");
-        print_boxed("Continue: c    Replace: r");
-        match read_input().as_str() {
-            "r" => continue,
-            _ => return sentence,
-        };
+// Answers from the earlier split fill any chunk that has none now.
+// Returns a note when answers added since the change are lost.
+fn revert_splits(sentence: &mut Sentence) -> Option<String> {
+    if sentence.current_split == 0 {
+        return None;
+    }
+    let old = sentence.splits.pop().expect("There is a split to revert");
+    sentence.current_split -= 1;
+    sentence.answer_history.truncate(sentence.current_split + 1);
+    let saved = if sentence.answer_history.len() > sentence.current_split {
+        sentence.answer_history.pop()
+    } else {
+        None
+    };
+    let dropped = realign_answers(sentence, &old);
+    if let Some(saved) = saved.filter(|saved| saved.answers.iter().any(|a| !a.is_empty())) {
+        let chunks = sentence.splits[sentence.current_split].len();
+        sentence.answers.resize_with(chunks, Vec::new);
+        sentence
+            .chunk_settings
+            .resize_with(chunks, ChunkSettings::default);
+        for (idx, group) in saved.answers.into_iter().enumerate().take(chunks) {
+            if sentence.answers[idx].is_empty() && !group.is_empty() {
+                sentence.answers[idx] = group;
+                sentence.chunk_settings[idx] =
+                    saved.chunk_settings.get(idx).cloned().unwrap_or_default();
+            }
+        }
     }
+    (dropped > 0).then(|| format!("The answers for {} changed chunks were removed.", dropped))
 }
 
-fn edit_sentence(sentence: &mut Sentence) -> &mut Sentence {
-    loop {
-        // clear the screen
-        print!("\x1B[2J");
-        print_boxed("Edit the sentence.");
-        print_enumerated(&sentence.splits[sentence.current_split]);
-        print_boxed("Reorder: r    Join: j    Accept: a");
-        match read_input().as_str() {
-            "j" => {
-                join_parts(sentence);
-                continue;
+// Makes `new_split` current, keeping the answers it replaces for a
+// revert.
+fn push_split(sentence: &mut Sentence, new_split: Vec<String>) -> Option<String> {
+    let old = sentence.splits[sentence.current_split].clone();
+    sentence
+        .answer_history
+        .resize_with(sentence.current_split, SavedAnswers::default);
+    sentence.answer_history.push(SavedAnswers {
+        answers: sentence.answers.clone(),
+        chunk_settings: sentence.chunk_settings.clone(),
+    });
+    sentence.splits.push(new_split);
+    sentence.current_split += 1;
+    let dropped = realign_answers(sentence, &old);
+    (dropped > 0).then(|| {
+        format!(
+            "The answers for {} changed chunks were set aside. Revert to bring them back.",
+            dropped
+        )
+    })
+}
+
+// Keeps the answers and chunk settings with their chunks after the
+// chunks change. Each chunk takes the answers of the first unused old
+// chunk with the same text, so reordered chunks keep theirs and joined
+// chunks start again. Returns how many chunks lost their answers.
+fn realign_answers(sentence: &mut Sentence, old: &[String]) -> usize {
+    if sentence.answers.is_empty() && sentence.chunk_settings.is_empty() {
+        return 0;
+    }
+    let mut answers: Vec<Option<Vec<AnswerOption>>> = mem::take(&mut sentence.answers)
+        .into_iter()
+        .map(Some)
+        .collect();
+    let mut settings: Vec<Option<ChunkSettings>> = mem::take(&mut sentence.chunk_settings)
+        .into_iter()
+        .map(Some)
+        .collect();
+    answers.resize_with(old.len(), || None);
+    settings.resize_with(old.len(), || None);
+    for chunk in sentence.splits[sentence.current_split].iter() {
+        let found = (0..old.len()).find(|idx| old[*idx] == *chunk && answers[*idx].is_some());
+        match found {
+            Some(idx) => {
+                sentence
+                    .answers
+                    .push(answers[idx].take().unwrap_or_default());
+                sentence
+                    .chunk_settings
+                    .push(settings[idx].take().unwrap_or_default());
             }
-            "r" => {
-                reorder_parts(sentence);
-                continue;
+            None => {
+                sentence.answers.push(Vec::new());
+                sentence.chunk_settings.push(ChunkSettings::default());
             }
-            _ => return sentence,
         }
     }
+    answers
+        .iter()
+        .flatten()
+        .filter(|group| !group.is_empty())
+        .count()
 }
 
-fn join_parts(sentence: &mut Sentence) -> &mut Sentence {
+fn get_sentence() -> Sentence {
     loop {
-        // clear the screen
-        print!("\x1B[2J");
-        print_boxed("Join the words.");
-        print_enumerated(&sentence.splits[sentence.current_split]);
-        print_boxed(
-            "Enter a word's number to join it with the next word.\nAccept: a     Revert: r",
-        );
-        let entry = read_input();
-        match entry.as_str() {
-            "a" => return sentence,
-            "r" => revert_splits(sentence),
-            "" => continue,
-            _ => {
-                let mut num = entry.parse::<usize>().unwrap();
-                println!("You entered: {}", num);
-                num -= 1;
-                apply_join(sentence, num);
-            }
-        }
+        print_boxed("Please enter your sentence.");
+        println!();
+        let sentence = Sentence::new(read_input());
+        println!("You entered: {}", sentence.initial_sentence);
+        println!();
+        print_key_help(&[("c", "Continue"), ("r", "Replace")]);
+        match read_input().as_str() {
+            "r" => continue,
+            _ => return sentence,
+        };
     }
 }
 
-fn apply_join(sentence: &mut Sentence, idx: usize) {
-    if idx + 2 > sentence.splits[sentence.current_split].len() {
-        println!("You cannot join the last word to a 'next word'. There is no 'next word'!");
-        return;
+// The chunk editors say why they refused, or else give a note on any
+// answers set aside, and leave showing it to the caller.
+fn apply_join(sentence: &mut Sentence, idx: usize) -> Result<Option<String>, &'static str> {
+    if idx.saturating_add(2) > sentence.splits[sentence.current_split].len() {
+        return Err("You cannot join the last word to a 'next word'. There is no 'next word'!");
     }
     let mut new_split: Vec<String> = Vec::new();
     let prev_split = &sentence.splits[sentence.current_split];
@@ -233,36 +457,15 @@ fn apply_join(sentence: &mut Sentence, idx: usize) {
             new_split.push(word.clone());
         }
     }
-    sentence.splits.push(new_split);
-    sentence.current_split += 1;
-}
-
-fn reorder_parts(sentence: &mut Sentence) -> &mut Sentence {
-    loop {
-        // clear the screen
-        print!("\x1B[2J");
-        print_boxed("Reorder the words.");
-        print_enumerated(&sentence.splits[sentence.current_split]);
-        print_boxed("Move up: u    Move down: d    Accept: a     Revert: r");
-        let entry = read_input();
-        match entry.as_str() {
-            "a" => return sentence,
-            "r" => revert_splits(sentence),
-            "u" => move_up(sentence, get_num_choice("Which word? ")),
-            "d" => apply_reorder(sentence, get_num_choice("Which word? ")),
-            _ => continue,
-        }
-    }
+    Ok(push_split(sentence, new_split))
 }
 
-fn move_up(sentence: &mut Sentence, idx: usize) {
+fn move_up(sentence: &mut Sentence, idx: usize) -> Result<Option<String>, &'static str> {
     if idx == 0 {
-        println!("You cannot move the first one earlier.");
-        return;
+        return Err("You cannot move the first one earlier.");
     }
-    if idx > sentence.splits[sentence.current_split].len() {
-        println!("That number is too high! Try again!");
-        return;
+    if idx >= sentence.splits[sentence.current_split].len() {
+        return Err("That number is too high! Try again!");
     }
     let mut new_split: Vec<String> = Vec::new();
     let prev_split = &sentence.splits[sentence.current_split];
@@ -278,14 +481,12 @@ fn move_up(sentence: &mut Sentence, idx: usize) {
             new_split.push(word.clone());
         }
     }
-    sentence.splits.push(new_split);
-    sentence.current_split += 1;
+    Ok(push_split(sentence, new_split))
 }
 
-fn apply_reorder(sentence: &mut Sentence, idx: usize) {
-    if idx + 2 > sentence.splits[sentence.current_split].len() {
-        println!("You cannot join the last word to a 'next word'. There is no 'next word'!");
-        return;
+fn apply_reorder(sentence: &mut Sentence, idx: usize) -> Result<Option<String>, &'static str> {
+    if idx.saturating_add(2) > sentence.splits[sentence.current_split].len() {
+        return Err("You cannot join the last word to a 'next word'. There is no 'next word'!");
     }
     let mut new_split: Vec<String> = Vec::new();
     let prev_split = &sentence.splits[sentence.current_split];
@@ -301,81 +502,103 @@ fn apply_reorder(sentence: &mut Sentence, idx: usize) {
             new_split.push(word.clone());
         }
     }
-    sentence.splits.push(new_split);
-    sentence.current_split += 1;
+    Ok(push_split(sentence, new_split))
 }
 
-fn set_answers(sentence: &mut Sentence) {
+// Punctuation chunks start out as fixed text in the target language.
+fn prepare_answers(sentence: &mut Sentence) {
     let max_ans_vecs = sentence.splits[sentence.current_split].len();
-    for _ in 0..max_ans_vecs {
-        let new_vec: Vec<AnswerOption> = Vec::new();
-        sentence.answers.push(new_vec);
-    }
-
-    loop {
-        // clear the screen
-        print!("\x1B[2J");
-        print_boxed("Enter some answers.");
-        print_enumerated_answers(&sentence);
-        print_boxed("Edit answers: e    Complete: c    Return to menu: m");
-        let entry = read_input();
-        match entry.as_str() {
-            "c" => {
-                // sentence.completed = true;
-                if check_for_complete(sentence) {
-                    break;
-                } else {
-                    continue;
-                }
-            }
-            "m" => {
-                break;
-            }
-            "e" => {
-                let idx = get_num_choice("Which no.? ");
-                add_answer_dialog(sentence, idx);
-            }
-            _ => continue,
+    sentence.answers.resize_with(max_ans_vecs, Vec::new);
+    for (chunk, group) in sentence.splits[sentence.current_split]
+        .iter()
+        .zip(sentence.answers.iter_mut())
+    {
+        if group.is_empty() && is_punctuation(chunk) {
+            group.push(AnswerOption {
+                is_question: false,
+                mark: 0,
+                answer: config().language.translate(chunk),
+                feedback: "###Not a question###".to_string(),
+            });
         }
     }
+    sentence
+        .chunk_settings
+        .resize_with(max_ans_vecs, ChunkSettings::default);
+}
+
+fn can_complete(sentence: &Sentence) -> bool {
+    !sentence.answers.is_empty() && !sentence.answers[0].is_empty()
 }
 
 fn check_for_complete(sentence: &mut Sentence) -> bool {
-    if sentence.answers[0].len() == 0 {
+    if !can_complete(sentence) {
         print_boxed(
             "You cannot mark this complete:\n\
                      you haven't entered any answers.\n\
                      Continue: c",
         );
         read_input();
-        return false;
+        false
     } else {
         sentence.completed = true;
-        return true;
+        true
     }
 }
 
-fn add_answer_dialog(sentence: &mut Sentence, idx: usize) {
-    loop {
-        // clear the screen
-        print!("\x1B[2J");
-        print_boxed("Enter/edit answers.");
-        print_answer_options(sentence, idx);
-        print_boxed("Add: a    Edit: e    Delete: d    Accept: RET");
-        match read_input().as_str() {
-            "a" => add_answer(sentence, idx),
-            "e" => edit_answer_dialog(sentence, idx),
-            "d" => delete_answer_dialog(sentence, idx),
-            "" => break,
-            _ => break,
+fn print_feedback_library() {
+    for (num, feedback) in FEEDBACK_LIBRARY.iter().enumerate() {
+        println!("{}. {}", num + 1, feedback);
+    }
+}
+
+fn library_feedback(choice: &str) -> Option<&'static str> {
+    match choice.parse::<usize>() {
+        Ok(num) if num > 0 => FEEDBACK_LIBRARY.get(num - 1).copied(),
+        _ => None,
+    }
+}
+
+fn feedback_completions() -> Vec<String> {
+    FEEDBACK_LIBRARY
+        .iter()
+        .map(|feedback| feedback.to_string())
+        .collect()
+}
+
+fn answer_completions(sentence: &Sentence, idx: usize) -> Vec<String> {
+    let mut completions = remembered_answers(&sentence.splits[sentence.current_split][idx]);
+    for option in &sentence.answers[idx] {
+        if !completions.contains(&option.answer) {
+            completions.push(option.answer.clone());
         }
     }
+    completions
 }
 
 fn add_answer(sentence: &mut Sentence, idx: usize) {
     print_boxed("Enter an answer.");
     // Get the answer
-    let answer = read_input();
+    let answer = read_input_with(answer_completions(sentence, idx));
+    remember_answer(&sentence.splits[sentence.current_split][idx], &answer);
+    let answeroption = choose_mark_and_feedback(answer);
+    sentence.answers[idx].push(answeroption);
+}
+
+// Puts a student's response in as an answer of its own, ahead of any
+// wildcard that catches it now.
+fn add_response_answer(sentence: &mut Sentence, idx: usize, response: &str) {
+    print_boxed(&format!("Answer: {}", response));
+    let answeroption = choose_mark_and_feedback(response.to_string());
+    let group = &mut sentence.answers[idx];
+    let pos = group
+        .iter()
+        .position(|answer| is_wildcard(&answer.answer))
+        .unwrap_or(group.len());
+    group.insert(pos, answeroption);
+}
+
+fn choose_mark_and_feedback(answer: String) -> AnswerOption {
     // Get the mark
     print_boxed("Choose a mark.");
     println!("1. 0%");
@@ -400,58 +623,26 @@ fn add_answer(sentence: &mut Sentence, idx: usize) {
     // Get feedback
     let mut flag: bool = true;
     print_boxed("Choose the feedback.");
-    println!("1. Try again!");
-    println!("2. Well done!");
-    println!("3. Look at your notes on nouns.");
-    println!("4. Look at your notes on verbs.");
-    println!("5. Look at your notes on adjectives.");
+    print_feedback_library();
     println!("6. ###Not a question###");
     println!("7. Input something else.");
     print_line();
     let feedback = match read_input().as_str() {
-        "1" => "Try again!".to_string(),
-        "2" => "Well done!".to_string(),
-        "3" => "Look at your notes on nouns.".to_string(),
-        "4" => "Look at your notes on verbs.".to_string(),
-        "5" => "Look at your notes on adjectives".to_string(),
         "6" => {
             flag = false;
             "###Not a question###".to_string()
         }
         "7" => {
             print!("Enter your feedback: ");
-            format!("{}", read_input())
+            read_input_with(feedback_completions())
         }
-        _ => "Try again!".to_string(),
+        choice => library_feedback(choice).unwrap_or("Try again!").to_string(),
     };
-    let answeroption = AnswerOption {
+    AnswerOption {
         is_question: flag,
-        mark: mark,
-        answer: answer,
-        feedback: feedback,
-    };
-    sentence.answers[idx].push(answeroption);
-}
-
-fn edit_answer_dialog(sentence: &mut Sentence, idx: usize) {
-    loop {
-        // clear the screen
-        print!("\x1B[2J");
-        print_boxed("Choose an answer.");
-        print_answer_options(sentence, idx);
-        print_boxed("Edit: num    Mark non-question: m    Delete: d    Accept: a");
-        let entry = read_input();
-        match entry.as_str() {
-            "d" => delete_answer_dialog(sentence, idx),
-            "a" => break,
-            "m" => mark_non_question(sentence, idx, get_num_choice("Which no.? ")),
-            "" => continue,
-            _ => {
-                // let opt = entry.trim().parse::<usize>().unwrap();
-                // edit_answer(sentence, idx, opt - 1);
-                edit_answer(sentence, idx, get_num_choice("Which no.? "));
-            }
-        }
+        mark,
+        answer,
+        feedback,
     }
 }
 
@@ -462,25 +653,9 @@ fn mark_non_question(sentence: &mut Sentence, idx: usize, opt: usize) {
     answer_struct.is_question = false;
 }
 
-fn delete_answer_dialog(sentence: &mut Sentence, idx: usize) {
-    loop {
-        // clear the screen
-        print!("\x1B[2J");
-        print_boxed("Delete an answer.");
-        print_answer_options(sentence, idx);
-        print_boxed("Delete: num    Accept: RET");
-        let entry = read_input();
-        match entry.as_str() {
-            "" => break,
-            _ => {
-                let opt = entry.trim().parse::<usize>().unwrap();
-                delete_answer(sentence, idx, opt - 1);
-            }
-        }
-    }
-}
-
 fn edit_answer(sentence: &mut Sentence, idx: usize, opt: usize) {
+    let completions = answer_completions(sentence, idx);
+    let chunk = sentence.splits[sentence.current_split][idx].clone();
     let answer_struct = &mut sentence.answers[idx][opt];
     let previous_mark = &mut answer_struct.mark;
     let previous_answer = &mut answer_struct.answer;
@@ -490,11 +665,12 @@ fn edit_answer(sentence: &mut Sentence, idx: usize, opt: usize) {
     print_boxed("Edit an answer.");
     // Get the answer
     println!("Answer: {}", previous_answer);
-    let mut rl = rustyline::Editor::<()>::new();
-    let readline = rl.readline_with_initial(">> ", (&previous_answer[..], ""));
-    match readline {
-        Ok(line) => *previous_answer = line,
-        Err(_) => println!("No input"),
+    let line = read_input_with_initial(previous_answer, completions);
+    if line.is_empty() {
+        println!("No input");
+    } else {
+        remember_answer(&chunk, &line);
+        *previous_answer = line;
     }
 
     // Get the mark
@@ -522,28 +698,141 @@ fn edit_answer(sentence: &mut Sentence, idx: usize, opt: usize) {
 
     // Get feedback
     print_boxed("Choose the feedback.");
-    println!("1. Try again!");
-    println!("2. Well done!");
-    println!("3. Look at your notes on nouns.");
-    println!("4. Look at your notes on verbs.");
-    println!("5. Look at your notes on adjectives.");
+    print_feedback_library();
     println!("6. Input something else.");
     print_line();
     println!("Current feedback: {}", previous_feedback);
     match read_input().as_str() {
-        "1" => *previous_feedback = "Try again!".to_string(),
-        "2" => *previous_feedback = "Well done!".to_string(),
-        "3" => *previous_feedback = "Look at your notes on nouns.".to_string(),
-        "4" => *previous_feedback = "Look at your notes on verbs.".to_string(),
-        "5" => *previous_feedback = "Look at your notes on adjectives".to_string(),
         "6" => {
             print!("Enter your feedback: ");
-            *previous_feedback = format!("{}", read_input());
+            *previous_feedback = read_input_with_initial(previous_feedback, feedback_completions());
+        }
+        choice => {
+            if let Some(feedback) = library_feedback(choice) {
+                *previous_feedback = feedback.to_string();
+            }
+        }
+    }
+}
+
+// `Some(None)` means the chunk should follow the bank default.
+fn choose_gap_type(current: &str, offer_default: bool) -> Option<Option<ClozeType>> {
+    print_boxed("Choose the gap type.");
+    for (num, kind) in ClozeType::ALL.iter().enumerate() {
+        println!("{}. {}", num + 1, kind.label());
+    }
+    if offer_default {
+        println!("{}. Bank default", ClozeType::ALL.len() + 1);
+    }
+    print_line();
+    println!("Current type: {}", current);
+    match read_input().parse::<usize>() {
+        Ok(num) if num > 0 && num <= ClozeType::ALL.len() => Some(Some(ClozeType::ALL[num - 1])),
+        Ok(num) if offer_default && num == ClozeType::ALL.len() + 1 => Some(None),
+        _ => None,
+    }
+}
+
+fn set_gap_type(sentence: &mut Sentence, idx: usize) -> bool {
+    let current = match sentence.chunk_settings[idx].gap_type {
+        Some(kind) => kind.label(),
+        None => "Bank default",
+    };
+    match choose_gap_type(current, true) {
+        Some(kind) => {
+            sentence.chunk_settings[idx].gap_type = kind;
+            true
+        }
+        None => false,
+    }
+}
+
+fn set_default_gap_type(bank: &mut Bank) -> bool {
+    match choose_gap_type(bank.default_gap_type.label(), false) {
+        Some(Some(kind)) => {
+            bank.default_gap_type = kind;
+            true
+        }
+        _ => false,
+    }
+}
+
+fn set_gap_weight(sentence: &mut Sentence, idx: usize) -> bool {
+    println!("Current weight: {}", sentence.gap_weight(idx));
+    print!("Enter a weight (blank for the default): ");
+    let line = read_input();
+    if line.is_empty() {
+        sentence.chunk_settings[idx].weight = None;
+        return true;
+    }
+    match line.parse::<u32>() {
+        Ok(weight) if weight > 0 => {
+            sentence.chunk_settings[idx].weight = Some(weight);
+            true
+        }
+        _ => {
+            println!("Weights must be whole numbers above 0.");
+            false
+        }
+    }
+}
+
+fn set_category(sentence: &mut Sentence, idx: usize) -> bool {
+    print_boxed("Choose the grammar category.");
+    for (num, category) in Category::ALL.iter().enumerate() {
+        println!("{}. {}", num + 1, category.label());
+    }
+    println!("{}. None", Category::ALL.len() + 1);
+    print_line();
+    match read_input().parse::<usize>() {
+        Ok(num) if num > 0 && num <= Category::ALL.len() => {
+            sentence.chunk_settings[idx].category = Some(Category::ALL[num - 1]);
+            true
+        }
+        Ok(num) if num == Category::ALL.len() + 1 => {
+            sentence.chunk_settings[idx].category = None;
+            true
+        }
+        _ => false,
+    }
+}
+
+fn edit_cloze_code(sentence: &mut Sentence, idx: usize, default: ClozeType) -> bool {
+    print_boxed("Edit the cloze code.");
+    let kind = sentence.gap_type(idx, default);
+    let weight = sentence.gap_weight(idx);
+    let code = read_cloze_code(&moodle_subquestion(kind, weight, &sentence.answers[idx]));
+    match parse_cloze(&code) {
+        Some((parsed_weight, parsed, options)) => {
+            sentence.answers[idx] = options;
+            if parsed != kind {
+                sentence.chunk_settings[idx].gap_type = Some(parsed);
+            }
+            if parsed_weight != weight {
+                sentence.chunk_settings[idx].weight = Some(parsed_weight);
+            }
+            true
+        }
+        None => {
+            print_boxed("That is not a valid cloze code.\nContinue: c");
+            read_input();
+            false
         }
-        _ => {}
     }
 }
 
 fn delete_answer(sentence: &mut Sentence, idx: usize, opt: usize) {
     sentence.answers[idx].remove(opt);
 }
+
+fn move_answer(sentence: &mut Sentence, idx: usize, from: usize, to: usize) {
+    let answer = sentence.answers[idx].remove(from);
+    sentence.answers[idx].insert(to, answer);
+}
+
+// Highest marks first with any wildcard answers at the end, so that
+// Moodle tries the exact answers before the catch-alls.
+fn sort_answers(sentence: &mut Sentence, idx: usize) {
+    sentence.answers[idx]
+        .sort_by_key(|answer| (is_wildcard(&answer.answer), std::cmp::Reverse(answer.mark)));
+}
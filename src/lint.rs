@@ -0,0 +1,91 @@
+use crate::cloze::ClozeType;
+use crate::grading::{gaps, shadowed, specific_first};
+use crate::printing::{announce, print_boxed, print_key_help};
+use crate::theme::{paint, Role};
+use crate::{read_input, Bank, Sentence};
+use std::mem;
+
+pub fn shadow_warnings(sentence: &Sentence, idx: usize, default: ClozeType) -> Vec<String> {
+    let options = &sentence.answers[idx];
+    shadowed(options, sentence.gap_type(idx, default))
+        .into_iter()
+        .map(|(later, earlier)| {
+            format!(
+                "Answer {} ({}) can never match: answer {} ({}) comes first and takes all its responses.",
+                later + 1,
+                options[later].answer,
+                earlier + 1,
+                options[earlier].answer
+            )
+        })
+        .collect()
+}
+
+pub fn print_shadow_warnings(sentence: &Sentence, idx: usize, default: ClozeType) {
+    let warnings = shadow_warnings(sentence, idx, default);
+    for warning in warnings.iter() {
+        println!(
+            "{}",
+            paint(Role::ZeroMark, &format!("Warning: {}", warning))
+        );
+    }
+    if !warnings.is_empty() {
+        println!();
+    }
+}
+
+// Moves specific answers ahead of the wildcards that would hide them.
+// Returns whether anything moved.
+pub fn reorder_specific_first(sentence: &mut Sentence, idx: usize, default: ClozeType) -> bool {
+    let order = specific_first(&sentence.answers[idx], sentence.gap_type(idx, default));
+    if order.iter().enumerate().all(|(pos, opt)| pos == *opt) {
+        return false;
+    }
+    let mut options: Vec<_> = mem::take(&mut sentence.answers[idx])
+        .into_iter()
+        .map(Some)
+        .collect();
+    sentence.answers[idx] = order
+        .iter()
+        .map(|opt| options[*opt].take().expect("Each answer is placed once"))
+        .collect();
+    true
+}
+
+// Checks every gap in the bank for answers that can never match and
+// offers to put them in a working order.
+pub fn lint(bank: &mut Bank) {
+    let mut lines = Vec::new();
+    for (num, question) in bank.questions.iter().enumerate() {
+        for gap in gaps(question) {
+            for warning in shadow_warnings(question, gap, bank.default_gap_type) {
+                lines.push(format!(
+                    "Question {}, {}: {}",
+                    num + 1,
+                    question.splits[question.current_split][gap],
+                    warning
+                ));
+            }
+        }
+    }
+    if lines.is_empty() {
+        print_boxed("No problems found.\nContinue: c");
+        read_input();
+        return;
+    }
+    print_boxed(&lines.join("\n"));
+    print_key_help(&[("f", "Fix order"), ("c", "Continue")]);
+    if read_input() != "f" {
+        return;
+    }
+    let default = bank.default_gap_type;
+    let mut moved = 0;
+    for question in bank.questions.iter_mut() {
+        for gap in gaps(question) {
+            if reorder_specific_first(question, gap, default) {
+                moved += 1;
+            }
+        }
+    }
+    announce(&format!("Answers reordered in {} gaps.", moved));
+}
@@ -0,0 +1,170 @@
+use crate::config::config;
+use serde::{Deserialize, Serialize};
+
+pub const FIELDS: [&str; 8] = [
+    "Default grade",
+    "Penalty (%)",
+    "ID number",
+    "Tags (comma separated)",
+    "Hints (separated by |)",
+    "General feedback",
+    "Model answer in general feedback (yes/no)",
+    "Model answer (blank to build it from the answers)",
+];
+
+// Fields that only make sense on a single question.
+pub const QUESTION_ONLY: [usize; 2] = [2, 7];
+
+// Moodle question settings. Anything left as `None` on a question falls
+// back to the bank defaults, and from there to Moodle's own defaults.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Metadata {
+    pub default_grade: Option<f32>,
+    pub penalty: Option<f32>,
+    pub idnumber: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub hints: Option<Vec<String>>,
+    pub general_feedback: Option<String>,
+    pub model_feedback: Option<bool>,
+    pub model_answer: Option<String>,
+}
+
+fn split_list(text: &str, sep: char) -> Vec<String> {
+    text.split(sep)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn parse_number(text: &str, max: f32, problem: &str) -> Result<f32, String> {
+    match text.parse::<f32>() {
+        Ok(value) if (0.0..=max).contains(&value) => Ok(value),
+        _ => Err(problem.to_string()),
+    }
+}
+
+impl Metadata {
+    pub fn merged(&self, defaults: &Metadata) -> Metadata {
+        Metadata {
+            default_grade: self.default_grade.or(defaults.default_grade),
+            penalty: self.penalty.or(defaults.penalty),
+            idnumber: self.idnumber.clone().or_else(|| defaults.idnumber.clone()),
+            tags: self.tags.clone().or_else(|| defaults.tags.clone()),
+            hints: self.hints.clone().or_else(|| defaults.hints.clone()),
+            general_feedback: self
+                .general_feedback
+                .clone()
+                .or_else(|| defaults.general_feedback.clone()),
+            model_feedback: self.model_feedback.or(defaults.model_feedback),
+            model_answer: self
+                .model_answer
+                .clone()
+                .or_else(|| defaults.model_answer.clone()),
+        }
+    }
+
+    // The value of a field as the author would type it.
+    pub fn show(&self, field: usize) -> Option<String> {
+        match field {
+            0 => self.default_grade.map(|grade| grade.to_string()),
+            1 => self.penalty.map(|penalty| penalty.to_string()),
+            2 => self.idnumber.clone(),
+            3 => self.tags.as_ref().map(|tags| tags.join(", ")),
+            4 => self.hints.as_ref().map(|hints| hints.join(" | ")),
+            5 => self.general_feedback.clone(),
+            6 => self
+                .model_feedback
+                .map(|shown| if shown { "yes" } else { "no" }.to_string()),
+            7 => self.model_answer.clone(),
+            _ => None,
+        }
+    }
+
+    // A blank entry clears the field so the default applies again.
+    pub fn set(&mut self, field: usize, text: &str) -> Result<(), String> {
+        let text = text.trim();
+        let blank = text.is_empty();
+        match field {
+            0 if blank => self.default_grade = None,
+            0 => {
+                let problem = "The default grade must be a number of 0 or more.";
+                self.default_grade = Some(parse_number(text, f32::MAX, problem)?);
+            }
+            1 if blank => self.penalty = None,
+            1 => {
+                let problem = "The penalty must be a percentage from 0 to 100.";
+                self.penalty = Some(parse_number(text, 100.0, problem)?);
+            }
+            2 => self.idnumber = Some(text.to_string()).filter(|_| !blank),
+            3 => self.tags = Some(split_list(text, ',')).filter(|_| !blank),
+            4 => self.hints = Some(split_list(text, '|')).filter(|_| !blank),
+            5 => self.general_feedback = Some(text.to_string()).filter(|_| !blank),
+            6 if blank => self.model_feedback = None,
+            6 => match text.to_lowercase().as_str() {
+                "yes" | "y" => self.model_feedback = Some(true),
+                "no" | "n" => self.model_feedback = Some(false),
+                _ => return Err("Please answer yes or no.".to_string()),
+            },
+            7 => self.model_answer = Some(text.to_string()).filter(|_| !blank),
+            _ => return Err("There is no such setting.".to_string()),
+        }
+        Ok(())
+    }
+}
+
+pub fn print_metadata(metadata: &Metadata, defaults: Option<&Metadata>) {
+    for (num, label) in FIELDS.iter().enumerate() {
+        let value = match (metadata.show(num), defaults.and_then(|d| d.show(num))) {
+            (Some(value), _) => value,
+            (None, Some(default)) => format!("{} (bank default)", default),
+            (None, None) if config().plain => "not set".to_string(),
+            (None, None) => "-".to_string(),
+        };
+        println!("{}. {}: {}", num + 1, label, value);
+    }
+    println!();
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// The settings part of a `<question>` element, from general feedback
+// through to the tags. When asked for, the model answer leads the
+// general feedback.
+pub fn metadata_xml(metadata: &Metadata, model_answer: &str) -> String {
+    let mut general_feedback = metadata.general_feedback.clone().unwrap_or_default();
+    if metadata.model_feedback == Some(true) {
+        general_feedback = format!("<p>{}</p>{}", model_answer, general_feedback);
+    }
+    let mut xml = format!(
+        "<generalfeedback format=\"html\">\n\
+         <text><![CDATA[{}]]></text>\n\
+         </generalfeedback>\n\
+         <defaultgrade>{:.7}</defaultgrade>\n\
+         <penalty>{:.7}</penalty>\n",
+        general_feedback,
+        metadata.default_grade.unwrap_or(1.0),
+        metadata.penalty.unwrap_or(100.0 / 3.0) / 100.0,
+    );
+    if let Some(idnumber) = &metadata.idnumber {
+        xml.push_str(&format!("<idnumber>{}</idnumber>\n", xml_escape(idnumber)));
+    }
+    for hint in metadata.hints.iter().flatten() {
+        xml.push_str(&format!(
+            "<hint format=\"html\">\n<text><![CDATA[{}]]></text>\n</hint>\n",
+            hint
+        ));
+    }
+    if let Some(tags) = metadata.tags.as_ref().filter(|tags| !tags.is_empty()) {
+        xml.push_str("<tags>\n");
+        for tag in tags {
+            xml.push_str(&format!("<tag><text>{}</text></tag>\n", xml_escape(tag)));
+        }
+        xml.push_str("</tags>\n");
+    }
+    xml
+}
@@ -0,0 +1,64 @@
+use crate::grading::{gaps, grade_gap, grade_question};
+use crate::printing::{cloze_preview, print_boxed, print_line};
+use crate::theme::{mark_role, paint};
+use crate::{read_input, Bank, Sentence};
+
+// Asks each question in turn, one gap at a time, and grades the replies
+// as Moodle would. Each question is scored out of 1.
+pub fn practise(bank: &Bank) {
+    let questions: Vec<&Sentence> = bank
+        .questions
+        .iter()
+        .filter(|q| !gaps(q).is_empty())
+        .collect();
+    if questions.is_empty() {
+        print_boxed("There are no questions with answers to practise.\nContinue: c");
+        read_input();
+        return;
+    }
+    let mut total = 0.0;
+    for (num, question) in questions.iter().enumerate() {
+        print_boxed(&format!(
+            "Question {} of {}\n{}",
+            num + 1,
+            questions.len(),
+            question.initial_sentence
+        ));
+        println!("{}", cloze_preview(question, bank.default_gap_type));
+        println!();
+        let mut responses = Vec::new();
+        for gap in gaps(question) {
+            let kind = question.gap_type(gap, bank.default_gap_type);
+            if kind.is_multichoice() {
+                for (opt, option) in question.answers[gap].iter().enumerate() {
+                    println!("  {}. {}", opt + 1, option.answer);
+                }
+            }
+            print!("{}: ", question.splits[question.current_split][gap]);
+            let response = read_input();
+            let grade = grade_gap(bank, question, gap, &response);
+            let mark = (grade.fraction * 100.0).round() as u8;
+            let feedback = match grade.option {
+                Some(_) => grade.feedback.as_str(),
+                None => "No answer matched.",
+            };
+            println!(
+                "{} {}",
+                paint(mark_role(mark), &format!("{}%", mark)),
+                feedback
+            );
+            responses.push(response);
+        }
+        let responses: Vec<&str> = responses.iter().map(String::as_str).collect();
+        let fraction = grade_question(bank, question, &responses).fraction;
+        println!("Question score: {:.0}%", fraction * 100.0);
+        print_line();
+        total += fraction;
+    }
+    print_boxed(&format!(
+        "Total: {:.2} out of {}\nContinue: c",
+        total,
+        questions.len()
+    ));
+    read_input();
+}
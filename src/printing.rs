@@ -1,10 +1,22 @@
+use crate::catchall::exported_answers;
+use crate::changes::{choose_questions, mark_exported, Format};
+use crate::cloze::{moodle_subquestion, ClozeType};
+use crate::config::config;
+use crate::grading::gaps;
+use crate::metadata::metadata_xml;
+use crate::punctuation::{end_mark, join_parts};
 use crate::read_input;
-use crate::AnswerOption;
-use crate::Sentence;
+use crate::stats::question_stats;
+use crate::template::{category_path, question_name, question_text};
+use crate::testcases::check_before_export;
+use crate::theme::{mark_role, paint, Role};
+use crate::{Bank, Sentence};
+use ratatui::crossterm::terminal;
 use std::fs::File;
 use std::io::{BufWriter, Write};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-pub fn print_enumerated(words: &Vec<String>) {
+pub fn print_enumerated(words: &[String]) {
     for (num, word) in words.iter().enumerate() {
         let num = num + 1;
         println!("{}: {}", num, word);
@@ -12,90 +24,383 @@ pub fn print_enumerated(words: &Vec<String>) {
 }
 
 pub fn print_enumerated_answers(sentence: &Sentence) {
+    let (answer_width, feedback_width) = column_widths();
+    println!("Total weight: {}", sentence.total_weight());
     for (num, word) in sentence.splits[sentence.current_split].iter().enumerate() {
-        println!("{}: {}", num + 1, word);
+        let fixed = sentence
+            .answers
+            .get(num)
+            .and_then(|group| group.first())
+            .is_some_and(|answer| !answer.is_question);
+        let settings = sentence.chunk_settings.get(num);
+        let mut details: Vec<String> = Vec::new();
+        if let Some(kind) = settings.and_then(|settings| settings.gap_type) {
+            details.push(kind.label().to_string());
+        }
+        if let Some(category) = settings.and_then(|settings| settings.category) {
+            details.push(category.label().to_string());
+        }
+        if sentence
+            .answers
+            .get(num)
+            .is_some_and(|group| !group.is_empty())
+            && !fixed
+        {
+            details.push(format!("weight {}", sentence.gap_weight(num)));
+        }
+        if fixed {
+            println!("{}", paint(Role::Fixed, &format!("{}: {}", num + 1, word)));
+        } else if !details.is_empty() {
+            println!("{}: {} ({})", num + 1, word, details.join(", "));
+        } else {
+            println!("{}: {}", num + 1, word);
+        }
         if num < sentence.answers.len() {
-            let max_rows = sentence.answers[num].len();
-            for i in 0..max_rows {
-                let answer = &sentence.answers[num][i];
-                println!(
-                    "   |{:5} | {:35}| {:29}",
-                    answer.mark, answer.answer, answer.feedback
+            for answer in &sentence.answers[num] {
+                if config().plain {
+                    println!(
+                        "   Answer: {}. Mark {}. Feedback: {}",
+                        answer.answer, answer.mark, answer.feedback
+                    );
+                    continue;
+                }
+                let role = if fixed {
+                    Some(Role::Fixed)
+                } else {
+                    Some(mark_role(answer.mark))
+                };
+                print_table_row(
+                    "   ",
+                    &[
+                        (&format!("{:4}", answer.mark), 4, role),
+                        (&answer.answer, answer_width, None),
+                        (&answer.feedback, feedback_width, None),
+                    ],
                 );
             }
         }
     }
 }
 
-pub fn print_answer_options(sentence: &mut Sentence, idx: usize) {
+pub fn print_answer_options(sentence: &Sentence, idx: usize, default: ClozeType) {
     print_boxed(
         format!(
-            "Question word: {}",
-            sentence.splits[sentence.current_split][idx]
+            "Question word: {}\nGap type: {}\nWeight: {} (question total {})",
+            sentence.splits[sentence.current_split][idx],
+            sentence.gap_type(idx, default).label(),
+            sentence.gap_weight(idx),
+            sentence.total_weight()
         )
         .as_str(),
     );
-    if sentence.answers[idx].len() == 0 {
-        println!("");
+    if sentence.answers[idx].is_empty() {
+        println!();
         println!("Enter your first answer.");
-        println!("");
-    } else {
-        // let max_rows = sentence.answers[idx].len();
-        println!(
-            "    +------+------------------------------------+------------------------------+"
-        );
-        println!(
-            "    | Mark | Answer                             | Feedback                     |"
-        );
-        println!(
-            "+---+------+------------------------------------+------------------------------+"
-        );
+        println!();
+    } else if config().plain {
         for (num, word) in sentence.answers[idx].iter().enumerate() {
-            // println!("{}. {:?}", num + 1, word);
             println!(
-                "|{:2}.|{:5} | {:35}| {:29}|",
+                "Answer {}: {}. Mark {}. Feedback: {}",
                 num + 1,
-                word.mark,
                 word.answer,
+                word.mark,
                 word.feedback
             );
         }
-        println!(
-            "+---+------+------------------------------------+------------------------------+"
+        println!();
+    } else {
+        let (answer_width, feedback_width) = column_widths();
+        let border = table_border(&[3, 4, answer_width, feedback_width]);
+        println!("{}", border);
+        print_table_row(
+            "",
+            &[
+                ("", 3, None),
+                ("Mark", 4, None),
+                ("Answer", answer_width, None),
+                ("Feedback", feedback_width, None),
+            ],
         );
-        println!("");
+        println!("{}", border);
+        for (num, word) in sentence.answers[idx].iter().enumerate() {
+            print_table_row(
+                "",
+                &[
+                    (&format!("{:2}.", num + 1), 3, None),
+                    (&format!("{:4}", word.mark), 4, Some(mark_role(word.mark))),
+                    (&word.answer, answer_width, None),
+                    (&word.feedback, feedback_width, None),
+                ],
+            );
+        }
+        println!("{}", border);
+        println!();
+    }
+}
+
+pub fn terminal_width() -> usize {
+    match terminal::size() {
+        Ok((cols, _)) if cols > 0 => (cols as usize).max(40),
+        _ => 80,
+    }
+}
+
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+pub fn pad(text: &str, width: usize) -> String {
+    let fill = width.saturating_sub(display_width(text));
+    format!("{}{}", text, " ".repeat(fill))
+}
+
+// Breaks text into lines no wider than `width` columns, splitting on
+// spaces where possible and inside over-long words otherwise.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    if display_width(text) <= width {
+        return vec![text.to_string()];
+    }
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if display_width(&candidate) <= width {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        line = word.to_string();
+        while display_width(&line) > width {
+            let (head, rest) = split_at_width(&line, width);
+            lines.push(head);
+            line = rest;
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn split_at_width(text: &str, width: usize) -> (String, String) {
+    let mut used = 0;
+    for (pos, c) in text.char_indices() {
+        let char_width = UnicodeWidthChar::width(c).unwrap_or(0);
+        if used + char_width > width && pos > 0 {
+            return (text[..pos].to_string(), text[pos..].to_string());
+        }
+        used += char_width;
+    }
+    (text.to_string(), String::new())
+}
+
+// The answer and feedback columns share whatever the terminal leaves
+// after the number and mark columns and the borders.
+fn column_widths() -> (usize, usize) {
+    let free = terminal_width().saturating_sub(20).max(20);
+    let answer = free * 11 / 20;
+    (answer, free - answer)
+}
+
+fn table_border(widths: &[usize]) -> String {
+    let mut border = String::from("+");
+    for width in widths {
+        border.push_str(&"-".repeat(width + 2));
+        border.push('+');
+    }
+    border
+}
+
+// Colour is applied after padding so escape codes never count
+// towards a cell's width.
+fn print_table_row(lead: &str, cells: &[(&str, usize, Option<Role>)]) {
+    let wrapped: Vec<Vec<String>> = cells
+        .iter()
+        .map(|(text, width, _)| wrap(text, *width))
+        .collect();
+    let height = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+    for row in 0..height {
+        let mut line = format!("{}|", lead);
+        for (lines, (_, width, role)) in wrapped.iter().zip(cells) {
+            let text = pad(lines.get(row).map_or("", String::as_str), *width);
+            let text = match role {
+                Some(role) => paint(*role, &text),
+                None => text,
+            };
+            line.push_str(&format!(" {} |", text));
+        }
+        println!("{}", line);
+    }
+}
+
+pub fn clear_screen() {
+    if !config().plain {
+        print!("\x1B[2J");
+    }
+}
+
+pub fn announce(message: &str) {
+    if config().plain {
+        println!("{}", message);
     }
 }
 
 pub fn print_boxed(content: &str) {
-    println!("{}{}{}", "+", "-".repeat(78), "+");
-    for line in content.split("\n") {
-        println!("| {:76} |", line);
+    if config().plain {
+        println!("{}", content);
+        return;
+    }
+    let inner = terminal_width() - 4;
+    println!("+{}+", "-".repeat(inner + 2));
+    for line in content.split('\n') {
+        for part in wrap(line, inner) {
+            println!("| {} |", pad(&part, inner));
+        }
+    }
+    println!("+{}+", "-".repeat(inner + 2));
+}
+
+pub fn print_breadcrumbs(crumbs: &[String]) {
+    if config().plain {
+        println!("Location: {}", crumbs.join(" > "));
+    } else {
+        println!(" {}", crumbs.join(" › "));
     }
-    println!("{}{}{}", "+", "-".repeat(78), "+");
+}
+
+pub fn print_key_help(keys: &[(&str, &str)]) {
+    if config().plain {
+        println!("Options:");
+        for (key, label) in keys {
+            println!("  {} - {}", key, label);
+        }
+        return;
+    }
+    let entries: Vec<String> = keys
+        .iter()
+        .map(|(key, label)| format!("{}: {}", label, key))
+        .collect();
+    // Cells fit the longest entry with a space to spare, so that no two
+    // entries run together.
+    let cell = entries
+        .iter()
+        .map(|entry| display_width(entry))
+        .max()
+        .unwrap_or(0)
+        .max(18)
+        + 1;
+    let per_row = ((terminal_width() - 4) / cell).max(1);
+    let rows: Vec<String> = entries
+        .chunks(per_row)
+        .map(|row| {
+            let cells: Vec<String> = row.iter().map(|entry| pad(entry, cell)).collect();
+            cells.concat().trim_end().to_string()
+        })
+        .collect();
+    print_boxed(&rows.join("\n"));
 }
 
 pub fn print_line() {
-    println!("{}", "-".repeat(80));
+    if config().plain {
+        println!();
+        return;
+    }
+    println!("{}", "-".repeat(terminal_width()));
 }
 
-pub fn print_questions(bank: &Vec<Sentence>) {
-    println!("");
-    if bank.len() == 0 {
+pub fn print_questions(bank: &Bank) {
+    println!();
+    if bank.questions.is_empty() {
         println!("Press a to enter your first question...");
     } else {
-        for (num, question) in bank.iter().enumerate() {
-            let status: &str = match question.completed {
-                true => "D",
-                false => "i",
+        for (num, question) in bank.questions.iter().enumerate() {
+            let exercise = question
+                .exercise
+                .and_then(|idx| bank.exercises.get(idx))
+                .map_or(String::new(), |exercise| format!(" [{}]", exercise.name));
+            let facility = question_stats(bank, question.id).map_or(String::new(), |stats| {
+                format!(" (facility {:.0}%)", stats.facility())
+            });
+            if config().plain {
+                let status = if question.completed {
+                    "done"
+                } else {
+                    "incomplete"
+                };
+                println!(
+                    "{}. {} ({}){}{}",
+                    num + 1,
+                    question.initial_sentence,
+                    status,
+                    exercise,
+                    facility
+                );
+                let model = question.model_answer();
+                if !model.is_empty() {
+                    println!("   Model answer: {}", model);
+                }
+                continue;
+            }
+            let (status, role) = match question.completed {
+                true => ("D", Role::Done),
+                false => ("i", Role::Incomplete),
             };
-            println!(" {} : {}. {}", status, num + 1, question.initial_sentence);
+            let line = format!(
+                " {} : {}. {}{}{}",
+                status,
+                num + 1,
+                question.initial_sentence,
+                exercise,
+                facility
+            );
+            println!("{}", paint(role, &line));
+            let model = question.model_answer();
+            if !model.is_empty() {
+                println!("        {}", paint(Role::Fixed, &model));
+            }
         }
     }
-    println!("");
+    println!();
+}
+
+pub fn print_exercises(bank: &Bank) {
+    println!();
+    if bank.exercises.is_empty() {
+        println!("Press a to add an exercise...");
+    }
+    for (num, exercise) in bank.exercises.iter().enumerate() {
+        let count = bank
+            .questions
+            .iter()
+            .filter(|question| question.exercise == Some(num))
+            .count();
+        println!(
+            "{}. Stage {}: {} ({} questions)",
+            num + 1,
+            exercise.stage,
+            exercise.name,
+            count
+        );
+    }
+    let unassigned = bank
+        .questions
+        .iter()
+        .filter(|question| question.exercise.is_none())
+        .count();
+    if unassigned > 0 {
+        println!("{} questions are not in an exercise.", unassigned);
+    }
+    println!();
 }
 
-pub fn move_print_questions(bank: &mut Vec<Sentence>, idx: usize) {
+pub fn move_print_questions(bank: &[Sentence], idx: usize) {
     let mut flag: &str;
     for (num, question) in bank.iter().enumerate() {
         if num == idx {
@@ -103,33 +408,171 @@ pub fn move_print_questions(bank: &mut Vec<Sentence>, idx: usize) {
         } else {
             flag = " ";
         }
+        if config().plain {
+            let selected = if num == idx { " (selected)" } else { "" };
+            println!("{}. {}{}", num + 1, question.initial_sentence, selected);
+            continue;
+        }
         println!(" {} | {}", flag, question.initial_sentence);
     }
 }
 
-pub fn print_to_file(bank: &Vec<Sentence>) {
+pub fn ask_export_details() -> (String, String) {
+    print!("Which stage is this for? ");
+    let stage_number = read_input();
+    print!("Please supply a exercise name. ");
+    let ex_name = read_input().replace(' ', "_");
+    (stage_number, ex_name)
+}
+
+pub struct ExportGroup<'a> {
+    pub stage: String,
+    pub name: String,
+    pub questions: Vec<&'a Sentence>,
+}
+
+// Gathers the questions of each exercise, asking for a stage and name
+// for any that belong to none, and then asks whether each exercise
+// should get a file of its own. Returns the file names with the groups
+// that go in each.
+pub fn export_groups<'a>(
+    bank: &'a Bank,
+    ids: &[u32],
+    base: &str,
+    extension: &str,
+) -> Vec<(String, Vec<ExportGroup<'a>>)> {
+    let mut groups: Vec<ExportGroup> = Vec::new();
+    for (idx, exercise) in bank.exercises.iter().enumerate() {
+        let questions: Vec<&Sentence> = bank
+            .questions
+            .iter()
+            .filter(|question| question.exercise == Some(idx) && ids.contains(&question.id))
+            .collect();
+        if !questions.is_empty() {
+            groups.push(ExportGroup {
+                stage: exercise.stage.clone(),
+                name: exercise.name.clone(),
+                questions,
+            });
+        }
+    }
+    let unassigned: Vec<&Sentence> = bank
+        .questions
+        .iter()
+        .filter(|question| question.exercise.is_none() && ids.contains(&question.id))
+        .collect();
+    if !unassigned.is_empty() {
+        if !groups.is_empty() {
+            println!("{} questions are not in an exercise.", unassigned.len());
+        }
+        let (stage, name) = ask_export_details();
+        groups.push(ExportGroup {
+            stage,
+            name,
+            questions: unassigned,
+        });
+    }
+
+    let mut per_file = false;
+    if groups.len() > 1 {
+        print_key_help(&[("o", "One file"), ("s", "One file per exercise")]);
+        per_file = read_input() == "s";
+    }
+    if !per_file {
+        return vec![(format!("{}.{}", base, extension), groups)];
+    }
+    groups
+        .into_iter()
+        .map(|group| {
+            let label: String = format!("{}_{}", group.stage, group.name)
+                .chars()
+                .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                .collect();
+            (format!("{}_{}.{}", base, label, extension), vec![group])
+        })
+        .collect()
+}
+
+pub const NO_GAPS: &str = "These questions have no gaps yet, so they were left out:";
+
+pub fn announce_export(files: &[(String, Vec<ExportGroup>)]) {
+    let count: usize = files
+        .iter()
+        .flat_map(|(_, groups)| groups)
+        .map(|group| group.questions.len())
+        .sum();
+    let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+    announce(&format!(
+        "Wrote {} questions to {}.",
+        count,
+        names.join(", ")
+    ));
+}
+
+// Takes the questions `unfit` picks out of the export and returns
+// `reason` with a line for each, or nothing if none were taken.
+pub fn leave_out(
+    bank: &Bank,
+    ids: &mut Vec<u32>,
+    unfit: impl Fn(&Sentence) -> bool,
+    reason: &str,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (num, question) in bank.questions.iter().enumerate() {
+        if ids.contains(&question.id) && unfit(question) {
+            lines.push(format!("  {}. {}", num + 1, question.initial_sentence));
+            ids.retain(|id| *id != question.id);
+        }
+    }
+    if !lines.is_empty() {
+        lines.insert(0, reason.to_string());
+    }
+    lines
+}
+
+pub fn print_left_out(mut lines: Vec<String>) {
+    if lines.is_empty() {
+        return;
+    }
+    lines.push("Continue: c".to_string());
+    print_boxed(&lines.join("\n"));
+    read_input();
+}
+
+pub fn print_to_file(bank: &mut Bank) {
+    let mut ids = choose_questions(bank, Format::Xml);
+    print_left_out(leave_out(
+        bank,
+        &mut ids,
+        |question| gaps(question).is_empty(),
+        NO_GAPS,
+    ));
+    if ids.is_empty() || !check_before_export(bank, &ids) {
+        return;
+    }
+
     // Put opening statement in xml file
-    // fields are stage_number, folder_name
-    macro_rules! xml_start {
-        ($arg1:expr, $arg2:expr) => {
+    let xml_start = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<quiz>\n";
+
+    // field is the category path
+    macro_rules! xml_category {
+        ($arg1:expr) => {
             format!(
-                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
-             <quiz>\n\
-             <!-- question: 0  -->\n\
+                "<!-- question: 0  -->\n\
              <question type=\"category\">\n\
              <category>\n\
-             <text>$course$/{}/Composition/{}</text>\n\
+             <text>{}</text>\n\
              </category>\n\
              </question>\n",
-                $arg1, $arg2
+                $arg1
             )
             .as_bytes()
         };
     }
 
-    // fields are question_number, question_name, question, questioncode
+    // fields are question_id, question_name, questiontext, metadata
     macro_rules! xml_question {
-        ($q_num:expr, $q_name:expr, $question:expr, $q_code:expr) => {
+        ($q_num:expr, $q_name:expr, $q_text:expr, $meta:expr) => {
             format!(
                 "<!-- question: {}  -->\n\
                  <question type=\"cloze\" > \n\
@@ -138,80 +581,118 @@ pub fn print_to_file(bank: &Vec<Sentence>) {
                  </name>\n\
                  <questiontext>\n\
                  <text>\n\
-                 <![CDATA[<p>{}</p>\n\
-                 <p><font size=\"4\" face=\"times new roman,times,serif\">{}.</font></p>]]>\n\
+                 <![CDATA[{}]]>\n\
                  </text>\n\
                  </questiontext>\n\
-                 <generalfeedback>\n\
-                 <text></text>\n\
-                 </generalfeedback>\n\
+                 {}\
                  <shuffleanswers>0</shuffleanswers>\n\
                  </question>\n",
-                $q_num, $q_name, $question, $q_code
-            ).as_bytes()
-        }
+                $q_num, $q_name, $q_text, $meta
+            )
+            .as_bytes()
+        };
     }
 
     let xml_end: String = String::from("</quiz>\n");
 
-    print!("Which stage is this for? ");
-    let stage_number = read_input();
-    print!("Please supply a exercise name. ");
-    let ex_name = read_input().replace(" ", "_");
-    print!("Please enter a question title base for moodle to use internally. ");
-    // let question_title = read_input();
-    let question_title = format!("{}_q", ex_name);
-
-    // set up Writer
-    let f = File::create("./upload.xml").expect("Unable to create file");
-    let mut writer = BufWriter::new(f);
-
-    // make initial write
-    writer
-        .write(xml_start!(stage_number, ex_name))
-        .expect("Unable to write xml start.");
-
-    let mut question_number: u32 = 1000;
-    let mut question_name: String;
-    let mut english: &str;
-    let mut question_code = String::new();
-    //Now loop over question data
-    for question in bank.iter() {
-        question_number += 1;
-        question_name = format!("{}_{}", question_title, question_number);
-        english = question.initial_sentence.as_str();
-        // question_code = moodle_shortanswer(&question.answers[num]);
-        question_code.clear();
-        for group in question.answers.iter() {
-            if group[0].is_question {
-                question_code = format!("{} {}", question_code, moodle_shortanswer(&group));
-            } else {
-                question_code = format!("{} {}", question_code, group[0].answer)
+    let files = export_groups(bank, &ids, "upload", "xml");
+    for (file_name, groups) in files.iter() {
+        // set up Writer
+        let f = File::create(file_name).expect("Unable to create file");
+        let mut writer = BufWriter::new(f);
+
+        // make initial write
+        writer
+            .write_all(xml_start.as_bytes())
+            .expect("Unable to write xml start.");
+
+        for group in groups {
+            let (stage_number, ex_name) = (&group.stage, &group.name);
+            writer
+                .write_all(xml_category!(category_path(stage_number, ex_name)))
+                .expect("Unable to write xml category.");
+
+            let mut question_number: u32 = 1000;
+            let mut q_name: String;
+            let mut english: &str;
+            let mut parts: Vec<String> = Vec::new();
+            //Now loop over question data
+            for question in group.questions.iter() {
+                question_number += 1;
+                q_name = question_name(stage_number, ex_name, question_number, question.id);
+                english = question.initial_sentence.as_str();
+                // The ID number follows the name unless the author set one,
+                // so it stays the same from one export to the next.
+                let mut metadata = question.metadata.merged(&bank.defaults);
+                metadata.idnumber.get_or_insert_with(|| q_name.clone());
+                parts.clear();
+                for (num, answers) in question.answers.iter().enumerate() {
+                    if answers.is_empty() {
+                        continue;
+                    }
+                    if answers[0].is_question {
+                        let kind = question.gap_type(num, bank.default_gap_type);
+                        let weight = question.gap_weight(num);
+                        let answers = exported_answers(bank, question, num);
+                        parts.push(moodle_subquestion(kind, weight, &answers));
+                    } else {
+                        parts.push(answers[0].answer.clone());
+                    }
+                }
+                writer
+                    .write_all(xml_question!(
+                        question.id,
+                        q_name,
+                        question_text(
+                            english,
+                            &join_parts(&parts),
+                            end_mark(&parts, &config().language),
+                            stage_number,
+                            ex_name,
+                            question_number,
+                            question.id
+                        ),
+                        metadata_xml(&metadata, &question.model_answer())
+                    ))
+                    .expect("Unable to write xml question.");
             }
         }
         writer
-            .write(xml_question!(
-                question_number,
-                question_name,
-                english,
-                question_code
-            ))
-            .expect("Unable to write xml question.");
-    }
-    writer
-        .write(xml_end.as_bytes())
-        .expect("Unable to write xml end.");
-    writer.flush().expect("Unable to write data.");
-}
-
-fn moodle_shortanswer(answers: &Vec<AnswerOption>) -> String {
-    let mut question_string = String::new();
-    for answer in answers {
-        question_string = format!(
-            "{}~%{}%{}#{}",
-            question_string, answer.mark, answer.answer, answer.feedback
-        );
+            .write_all(xml_end.as_bytes())
+            .expect("Unable to write xml end.");
+        writer.flush().expect("Unable to write data.");
+    }
+    announce_export(&files);
+    mark_exported(bank, &ids, Format::Xml);
+}
+
+// Dropdowns show a marker and radio buttons list every option, much as
+// Moodle will lay them out.
+pub fn cloze_preview(sentence: &Sentence, default: ClozeType) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for (num, chunk) in sentence.splits[sentence.current_split].iter().enumerate() {
+        let part = match sentence.answers.get(num) {
+            Some(group) if !group.is_empty() && !group[0].is_question => group[0].answer.clone(),
+            Some(group) if !group.is_empty() => {
+                let width = group
+                    .iter()
+                    .map(|answer| answer.answer.chars().count())
+                    .max()
+                    .unwrap_or(0);
+                match sentence.gap_type(num, default) {
+                    ClozeType::MultiChoiceHorizontal | ClozeType::MultiChoiceVertical => group
+                        .iter()
+                        .map(|answer| format!("( ) {}", answer.answer))
+                        .collect::<Vec<String>>()
+                        .join(" "),
+                    kind if kind.is_multichoice() => format!("[{} v]", "_".repeat(width.max(5))),
+                    _ => format!("[{}]", "_".repeat(width.max(5))),
+                }
+            }
+            _ => format!("[?{}]", chunk),
+        };
+        parts.push(part);
     }
-    // format!("{{1:MULTICHOICE:{}}}", question_string)
-    format!("{{1:SHORTANSWER:{}}}", question_string)
+    let end = end_mark(&parts, &config().language);
+    format!("{}{}", join_parts(&parts), end)
 }
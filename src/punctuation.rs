@@ -0,0 +1,144 @@
+// How English punctuation is written in the target language. Greek
+// questions end in `;` and both colons and semicolons become the ano
+// teleia.
+pub struct Language {
+    pub full_stop: String,
+    pub question: String,
+    pub exclamation: String,
+    pub colon: String,
+    pub semicolon: String,
+}
+
+const MARKS: [char; 14] = [
+    '.', ',', ';', ':', '!', '?', '"', '(', ')', '[', ']', '“', '”', '…',
+];
+const OPENING: [char; 3] = ['(', '[', '“'];
+
+impl Language {
+    pub fn named(name: &str) -> Option<Self> {
+        let marks: [&str; 5] = match name {
+            "greek" => [".", ";", "!", "·", "·"],
+            "latin" => [".", "?", "!", ":", ";"],
+            _ => return None,
+        };
+        Some(Self {
+            full_stop: marks[0].to_string(),
+            question: marks[1].to_string(),
+            exclamation: marks[2].to_string(),
+            colon: marks[3].to_string(),
+            semicolon: marks[4].to_string(),
+        })
+    }
+
+    // Overrides one mark from a `punctuation.<mark> = <text>` config line.
+    pub fn set(&mut self, mark: &str, value: &str) {
+        let target = match mark {
+            "full_stop" => &mut self.full_stop,
+            "question" => &mut self.question,
+            "exclamation" => &mut self.exclamation,
+            "colon" => &mut self.colon,
+            "semicolon" => &mut self.semicolon,
+            _ => return,
+        };
+        *target = value.to_string();
+    }
+
+    pub fn translate(&self, text: &str) -> String {
+        let mut translated = String::new();
+        for c in text.chars() {
+            match c {
+                '.' => translated.push_str(&self.full_stop),
+                '?' => translated.push_str(&self.question),
+                '!' => translated.push_str(&self.exclamation),
+                ':' => translated.push_str(&self.colon),
+                ';' => translated.push_str(&self.semicolon),
+                _ => translated.push(c),
+            }
+        }
+        translated
+    }
+}
+
+pub fn is_punctuation(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| MARKS.contains(&c) || c == '·' || c == '\u{37e}')
+}
+
+// Splits on whitespace and then pulls leading and trailing punctuation
+// off each word into chunks of its own.
+pub fn tokenise(entry: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in entry.split_whitespace() {
+        let core = word.trim_start_matches(MARKS);
+        let lead = &word[..word.len() - core.len()];
+        let trimmed = core.trim_end_matches(MARKS);
+        let trail = &core[trimmed.len()..];
+        for part in [lead, trimmed, trail] {
+            if !part.is_empty() {
+                tokens.push(part.to_string());
+            }
+        }
+    }
+    tokens
+}
+
+// Whether `part` follows straight on from the text before it, as
+// closing punctuation does and anything after an opening bracket. A
+// straight quote has no direction of its own, so it opens when the text
+// before has an even number of them and closes otherwise.
+pub fn attaches(before: &str, part: &str) -> bool {
+    let quote_open = before.matches('"').count() % 2 == 1;
+    let opens = part.starts_with(OPENING) || (part.starts_with('"') && !quote_open);
+    before.is_empty()
+        || before.ends_with(OPENING)
+        || (before.ends_with('"') && quote_open)
+        || (is_punctuation(part) && !opens)
+}
+
+pub fn join_parts(parts: &[String]) -> String {
+    let mut joined = String::new();
+    for part in parts {
+        if !attaches(&joined, part) {
+            joined.push(' ');
+        }
+        joined.push_str(part);
+    }
+    joined
+}
+
+// A sentence that does not finish with punctuation of its own gets the
+// target language's full stop.
+pub fn end_mark<'a>(parts: &[String], language: &'a Language) -> &'a str {
+    match parts.last() {
+        Some(last) if is_punctuation(last) => "",
+        _ => &language.full_stop,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joined(entry: &str) -> String {
+        join_parts(&tokenise(entry))
+    }
+
+    #[test]
+    fn straight_quotes_open_and_close_in_turn() {
+        assert_eq!(joined("He said \"hello\"."), "He said \"hello\".");
+        assert_eq!(
+            joined("\"Yes,\" he said, \"twice\"."),
+            "\"Yes,\" he said, \"twice\"."
+        );
+    }
+
+    #[test]
+    fn brackets_and_curly_quotes_keep_their_direction() {
+        assert_eq!(
+            joined("He (the man) said “hello”."),
+            "He (the man) said “hello”."
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use crate::grading::{gaps, grade_gap, is_wildcard, locate_gap};
+use crate::printing::print_boxed;
+use crate::template::question_name;
+use crate::{read_input, Bank, Sentence};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// A response that no answer of ours picked out, or that only a
+// wildcard caught, with how many students gave it. The chunk's text is
+// kept to find the gap again after the chunks change.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CommonResponse {
+    pub question: u32,
+    pub gap: usize,
+    #[serde(default)]
+    pub chunk: String,
+    pub response: String,
+    pub count: u32,
+}
+
+// One student's response to one of our questions, a part per gap.
+pub struct StudentResponse {
+    pub question: usize,
+    pub parts: Vec<String>,
+}
+
+pub struct Report {
+    pub responses: Vec<StudentResponse>,
+    pub unmatched: usize,
+}
+
+// The names the questions had when the whole bank was last exported:
+// the ID number if the author set one and otherwise the name the
+// template gives them.
+fn export_names(bank: &Bank) -> Vec<Option<String>> {
+    let mut numbers = vec![1000; bank.exercises.len()];
+    bank.questions
+        .iter()
+        .map(|question| {
+            let exercise = question
+                .exercise
+                .and_then(|idx| Some((idx, bank.exercises.get(idx)?)));
+            if let Some((idx, _)) = exercise {
+                numbers[idx] += 1;
+            }
+            if let Some(idnumber) = &question.metadata.idnumber {
+                return Some(idnumber.clone());
+            }
+            let (idx, exercise) = exercise?;
+            Some(question_name(
+                &exercise.stage,
+                &exercise.name,
+                numbers[idx],
+                question.id,
+            ))
+        })
+        .collect()
+}
+
+// Moodle gives either the question's name or its text with the gaps
+// filled in, which starts with our English sentence. The longest
+// sentence wins so that one question is not mistaken for another that
+// begins the same way.
+fn find_question(bank: &Bank, names: &[Option<String>], text: &str) -> Option<usize> {
+    let text = text.trim();
+    if let Some(idx) = names.iter().position(|name| name.as_deref() == Some(text)) {
+        return Some(idx);
+    }
+    bank.questions
+        .iter()
+        .enumerate()
+        .filter(|(_, question)| {
+            let english = question.initial_sentence.trim();
+            !english.is_empty() && text.starts_with(english)
+        })
+        .max_by_key(|(_, question)| question.initial_sentence.trim().len())
+        .map(|(idx, _)| idx)
+}
+
+// Cloze responses read `part 1: ...; part 2: ...`, leaving out the parts
+// the student did not answer. Anything else is a single part.
+pub fn split_parts(summary: &str) -> Vec<String> {
+    let mut markers: Vec<(usize, usize, usize)> = Vec::new();
+    for (pos, _) in summary.match_indices("part ") {
+        if pos != 0 && !summary[..pos].ends_with("; ") {
+            continue;
+        }
+        let rest = &summary[pos + 5..];
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 || !rest[digits..].starts_with(": ") {
+            continue;
+        }
+        let num: usize = rest[..digits].parse().unwrap_or(0);
+        let start = if pos == 0 { 0 } else { pos - 2 };
+        markers.push((start, pos + 5 + digits + 2, num));
+    }
+    if markers.first().is_none_or(|marker| marker.0 != 0) {
+        return vec![summary.trim().to_string()];
+    }
+    let mut parts = Vec::new();
+    for (idx, (_, content, num)) in markers.iter().enumerate() {
+        let end = markers.get(idx + 1).map_or(summary.len(), |next| next.0);
+        if *num > parts.len() {
+            parts.resize(*num, String::new());
+        }
+        if *num > 0 {
+            parts[num - 1] = summary[*content..end].trim().to_string();
+        }
+    }
+    parts
+}
+
+// Reads a Moodle responses report downloaded with the question text
+// shown, so that each `Response N` column has a `Question N` beside it.
+pub fn read_report(bank: &Bank, path: &str) -> Result<Report, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|error| format!("Unable to read {}: {}", path, error))?;
+    let headers = reader
+        .headers()
+        .map_err(|error| format!("Unable to read {}: {}", path, error))?
+        .clone();
+    let mut columns: Vec<(usize, usize)> = Vec::new();
+    for (col, header) in headers.iter().enumerate() {
+        if let Some(slot) = header.trim().strip_prefix("Response ") {
+            let question = format!("Question {}", slot);
+            if let Some(text_col) = headers.iter().position(|h| h.trim() == question) {
+                columns.push((text_col, col));
+            }
+        }
+    }
+    if columns.is_empty() {
+        return Err(
+            "The report has no question text. Download it with the question text shown."
+                .to_string(),
+        );
+    }
+
+    let names = export_names(bank);
+    let mut report = Report {
+        responses: Vec::new(),
+        unmatched: 0,
+    };
+    for record in reader.records() {
+        let record = record.map_err(|error| format!("Unable to read {}: {}", path, error))?;
+        for (text_col, col) in columns.iter() {
+            let (Some(text), Some(summary)) = (record.get(*text_col), record.get(*col)) else {
+                continue;
+            };
+            if text.trim().is_empty() || summary.trim().is_empty() || summary.trim() == "-" {
+                continue;
+            }
+            match find_question(bank, &names, text) {
+                Some(question) => report.responses.push(StudentResponse {
+                    question,
+                    parts: split_parts(summary),
+                }),
+                None => report.unmatched += 1,
+            }
+        }
+    }
+    Ok(report)
+}
+
+// Whether a response needs an answer of its own: nothing matches it
+// yet, or only a wildcard does.
+fn uncaught(question: &Sentence, gap: usize, response: &str, bank: &Bank) -> bool {
+    match grade_gap(bank, question, gap, response).option {
+        Some(option) => is_wildcard(&option.answer),
+        None => true,
+    }
+}
+
+pub fn import_responses(bank: &mut Bank) {
+    print!("Which file? ");
+    let path = read_input();
+    let report = match read_report(bank, path.trim()) {
+        Ok(report) => report,
+        Err(problem) => {
+            print_boxed(&format!("{}\nContinue: c", problem));
+            read_input();
+            return;
+        }
+    };
+    let mut counts: HashMap<(u32, usize, String), u32> = HashMap::new();
+    let mut chunks: HashMap<(u32, usize), String> = HashMap::new();
+    for student in report.responses.iter() {
+        let question = &bank.questions[student.question];
+        for (gap, part) in gaps(question).into_iter().zip(&student.parts) {
+            if !part.is_empty() && uncaught(question, gap, part, bank) {
+                *counts.entry((question.id, gap, part.clone())).or_insert(0) += 1;
+                chunks
+                    .entry((question.id, gap))
+                    .or_insert_with(|| question.splits[question.current_split][gap].clone());
+            }
+        }
+    }
+    let mut common: Vec<CommonResponse> = counts
+        .into_iter()
+        .map(|((question, gap, response), count)| CommonResponse {
+            question,
+            gap,
+            chunk: chunks[&(question, gap)].clone(),
+            response,
+            count,
+        })
+        .collect();
+    common.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then(a.question.cmp(&b.question))
+            .then(a.gap.cmp(&b.gap))
+            .then(a.response.cmp(&b.response))
+    });
+    let mut summary = format!(
+        "Read {} responses and found {} to review.",
+        report.responses.len(),
+        common.len()
+    );
+    if report.unmatched > 0 {
+        summary.push_str(&format!(
+            "\n{} responses were to questions not in this bank.",
+            report.unmatched
+        ));
+    }
+    bank.common_responses = common;
+    print_boxed(&format!("{}\nContinue: c", summary));
+    read_input();
+}
+
+// The bank index and gap of a response to review, if the question is
+// still there and still has a gap with the same chunk.
+pub fn response_target(bank: &Bank, common: &CommonResponse) -> Option<(usize, usize)> {
+    let idx = bank
+        .questions
+        .iter()
+        .position(|question| question.id == common.question)?;
+    let gap = locate_gap(&bank.questions[idx], common.gap, &common.chunk)?;
+    Some((idx, gap))
+}
+
+pub fn print_common_responses(bank: &Bank) {
+    if bank.common_responses.is_empty() {
+        println!("Nothing to review. Import a Moodle responses report first.");
+    }
+    for (num, common) in bank.common_responses.iter().enumerate() {
+        let Some((idx, gap)) = response_target(bank, common) else {
+            println!(
+                "{}. {} (its gap is no longer in the bank)",
+                num + 1,
+                common.response
+            );
+            continue;
+        };
+        let question = &bank.questions[idx];
+        let grade = grade_gap(bank, question, gap, &common.response);
+        let caught = match grade.option {
+            Some(option) => format!("{} gives {}%", option.answer, option.mark),
+            None => "no answer matches".to_string(),
+        };
+        println!(
+            "{}. Question {}, {}: {} x{} ({})",
+            num + 1,
+            idx + 1,
+            question.splits[question.current_split][gap],
+            common.response,
+            common.count,
+            caught
+        );
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_cloze_responses_into_parts() {
+        assert_eq!(
+            split_parts("part 1: οἱ ἄνδρες; part 2: πάρεισι"),
+            ["οἱ ἄνδρες", "πάρεισι"]
+        );
+    }
+
+    #[test]
+    fn unanswered_parts_are_blank() {
+        assert_eq!(split_parts("part 2: πάρεισι"), ["", "πάρεισι"]);
+    }
+
+    #[test]
+    fn other_responses_are_a_single_part() {
+        assert_eq!(split_parts(" δύο "), ["δύο"]);
+        assert_eq!(split_parts("a part 1: b"), ["a part 1: b"]);
+    }
+
+    #[test]
+    fn markers_must_follow_a_separator() {
+        assert_eq!(
+            split_parts("part 1: the part 2: x; part 2: y"),
+            ["the part 2: x", "y"]
+        );
+    }
+}
@@ -0,0 +1,724 @@
+use crate::catchall::{print_catch_all, print_catch_all_preview, CatchAll, CATCH_ALL_FIELDS};
+use crate::config::config;
+use crate::gift::{import_gift_file, print_gift_file};
+use crate::lint::{lint, print_shadow_warnings, reorder_specific_first};
+use crate::metadata::{print_metadata, Metadata, FIELDS, QUESTION_ONLY};
+use crate::practice::practise;
+use crate::printing::{
+    announce, clear_screen, move_print_questions, print_answer_options, print_boxed,
+    print_breadcrumbs, print_enumerated, print_enumerated_answers, print_exercises, print_key_help,
+    print_questions, print_to_file,
+};
+use crate::responses::{import_responses, print_common_responses, response_target};
+use crate::stats::{clear_stats, import_stats, print_gap_stats, print_stats};
+use crate::storage::AutoSave;
+use crate::testcases::{add_test_case, print_test_cases, run_test_cases};
+use crate::{
+    add_answer, add_exercise, add_response_answer, apply_join, apply_reorder, assign_exercise,
+    check_for_complete, delete_answer, delete_exercise, edit_answer, edit_cloze_code,
+    get_num_choice, get_sentence, input_closed, mark_non_question, move_answer, move_up,
+    prepare_answers, read_input, read_input_with_initial, revert_splits, set_category,
+    set_default_gap_type, set_gap_type, set_gap_weight, sort_answers, Bank,
+};
+
+// Each screen carries the indices it works on: the question and,
+// where relevant, the chunk of that question.
+pub enum Screen {
+    Bank,
+    MoveQuestion(usize),
+    EditSentence(usize),
+    JoinParts(usize),
+    ReorderParts(usize),
+    SetAnswers(usize),
+    AnswerOptions(usize, usize),
+    EditAnswers(usize, usize),
+    DeleteAnswers(usize, usize),
+    MoveAnswer(usize, usize, usize),
+    QuestionSettings(usize),
+    TestCases(usize),
+    BankDefaults,
+    CatchAllSettings,
+    Exercises,
+    Responses,
+    Stats,
+}
+
+pub enum Nav {
+    Stay,
+    Push(Screen),
+    Replace(Screen),
+    Back,
+    Home,
+    Quit,
+}
+
+impl Screen {
+    fn crumb(&self) -> String {
+        match self {
+            Screen::Bank => "Bank".to_string(),
+            Screen::MoveQuestion(_) => "Move".to_string(),
+            Screen::EditSentence(q) | Screen::SetAnswers(q) => format!("Question {}", q + 1),
+            Screen::JoinParts(_) => "Join".to_string(),
+            Screen::ReorderParts(_) => "Reorder".to_string(),
+            Screen::AnswerOptions(_, c) => format!("Chunk {}", c + 1),
+            Screen::EditAnswers(..) => "Edit".to_string(),
+            Screen::DeleteAnswers(..) => "Delete".to_string(),
+            Screen::MoveAnswer(..) => "Move".to_string(),
+            Screen::QuestionSettings(_) => "Moodle settings".to_string(),
+            Screen::TestCases(_) => "Test cases".to_string(),
+            Screen::BankDefaults => "Defaults".to_string(),
+            Screen::CatchAllSettings => "Catch-all".to_string(),
+            Screen::Exercises => "Exercises".to_string(),
+            Screen::Responses => "Responses".to_string(),
+            Screen::Stats => "Statistics".to_string(),
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Screen::Bank => "Sentences into Greek",
+            Screen::MoveQuestion(_) => "Move question",
+            Screen::EditSentence(_) => "Edit the sentence.",
+            Screen::JoinParts(_) => "Join the words.",
+            Screen::ReorderParts(_) => "Reorder the words.",
+            Screen::SetAnswers(_) => "Enter some answers.",
+            Screen::AnswerOptions(..) => "Enter/edit answers.",
+            Screen::EditAnswers(..) => "Choose an answer.",
+            Screen::DeleteAnswers(..) => "Delete an answer.",
+            Screen::MoveAnswer(..) => "Move the answer.",
+            Screen::QuestionSettings(_) => "Moodle settings for this question.",
+            Screen::TestCases(_) => "Responses and the grades they should get.",
+            Screen::BankDefaults => "Moodle settings for every question.",
+            Screen::CatchAllSettings => "The wrong answer added to every gap.",
+            Screen::Exercises => "Exercises in this bank.",
+            Screen::Responses => "Common student responses to review.",
+            Screen::Stats => "How students did.",
+        }
+    }
+
+    fn keys(&self) -> Vec<(&'static str, &'static str)> {
+        match self {
+            Screen::Bank => vec![
+                ("a", "Add question"),
+                ("e", "Edit question"),
+                ("d", "Delete question"),
+                ("m", "Move question"),
+                ("p", "Print to file"),
+                ("g", "Export GIFT"),
+                ("i", "Import GIFT"),
+                ("t", "Gap type"),
+                ("o", "Defaults"),
+                ("x", "Exercises"),
+                ("r", "Practise"),
+                ("v", "Run test cases"),
+                ("l", "Lint"),
+                ("w", "Student responses"),
+                ("y", "Statistics"),
+                ("s", "Start again"),
+                ("q", "Quit"),
+            ],
+            Screen::MoveQuestion(_) => vec![("u", "Move up"), ("d", "Move down"), ("a", "Accept")],
+            Screen::EditSentence(_) => vec![("r", "Reorder"), ("j", "Join"), ("a", "Accept")],
+            Screen::JoinParts(_) => vec![("num", "Join next"), ("r", "Revert"), ("a", "Accept")],
+            Screen::ReorderParts(_) => vec![
+                ("u", "Move up"),
+                ("d", "Move down"),
+                ("r", "Revert"),
+                ("a", "Accept"),
+            ],
+            Screen::SetAnswers(_) => vec![
+                ("e", "Edit answers"),
+                ("o", "Moodle settings"),
+                ("v", "Test cases"),
+                ("c", "Complete"),
+                ("m", "Menu"),
+            ],
+            Screen::AnswerOptions(..) => vec![
+                ("a", "Add"),
+                ("e", "Edit"),
+                ("d", "Delete"),
+                ("c", "Edit code"),
+                ("t", "Gap type"),
+                ("w", "Weight"),
+                ("k", "Category"),
+                ("m", "Move"),
+                ("s", "Sort"),
+                ("f", "Fix order"),
+                ("RET", "Accept"),
+            ],
+            Screen::EditAnswers(..) => vec![
+                ("num", "Edit"),
+                ("m", "Non-question"),
+                ("d", "Delete"),
+                ("a", "Accept"),
+            ],
+            Screen::DeleteAnswers(..) => vec![("num", "Delete"), ("RET", "Accept")],
+            Screen::MoveAnswer(..) => vec![
+                ("u", "Move up"),
+                ("d", "Move down"),
+                ("num", "Move to"),
+                ("a", "Accept"),
+            ],
+            Screen::QuestionSettings(_) | Screen::CatchAllSettings => {
+                vec![("num", "Change"), ("RET", "Accept")]
+            }
+            Screen::BankDefaults => vec![
+                ("num", "Change"),
+                ("k", "Catch-all answer"),
+                ("RET", "Accept"),
+            ],
+            Screen::TestCases(_) => vec![("a", "Add"), ("d", "Delete"), ("RET", "Accept")],
+            Screen::Responses => vec![
+                ("i", "Import report"),
+                ("num", "Add as answer"),
+                ("d", "Dismiss"),
+                ("c", "Clear"),
+                ("RET", "Accept"),
+            ],
+            Screen::Stats => vec![("i", "Import report"), ("c", "Clear"), ("RET", "Accept")],
+            Screen::Exercises => vec![
+                ("a", "Add"),
+                ("d", "Delete"),
+                ("u", "Use for questions"),
+                ("RET", "Accept"),
+            ],
+        }
+    }
+
+    fn render(&self, bank: &Bank) {
+        match *self {
+            Screen::Bank => {
+                if !config().plain {
+                    println!(" ~i: incomplete ~D: done");
+                }
+                print_questions(bank);
+            }
+            Screen::MoveQuestion(idx) => move_print_questions(&bank.questions, idx),
+            Screen::EditSentence(q) | Screen::JoinParts(q) | Screen::ReorderParts(q) => {
+                let sentence = &bank.questions[q];
+                print_enumerated(&sentence.splits[sentence.current_split]);
+            }
+            Screen::SetAnswers(q) => print_enumerated_answers(&bank.questions[q]),
+            Screen::AnswerOptions(q, c)
+            | Screen::EditAnswers(q, c)
+            | Screen::DeleteAnswers(q, c) => {
+                print_answer_options(&bank.questions[q], c, bank.default_gap_type);
+                print_shadow_warnings(&bank.questions[q], c, bank.default_gap_type);
+                print_catch_all_preview(bank, &bank.questions[q], c);
+                print_gap_stats(bank, q, c);
+            }
+            Screen::MoveAnswer(q, c, opt) => {
+                print_answer_options(&bank.questions[q], c, bank.default_gap_type);
+                print_shadow_warnings(&bank.questions[q], c, bank.default_gap_type);
+                println!(
+                    "Moving answer {}: {}",
+                    opt + 1,
+                    bank.questions[q].answers[c][opt].answer
+                );
+            }
+            Screen::QuestionSettings(q) => {
+                print_metadata(&bank.questions[q].metadata, Some(&bank.defaults))
+            }
+            Screen::TestCases(q) => print_test_cases(bank, &bank.questions[q]),
+            Screen::BankDefaults => print_metadata(&bank.defaults, None),
+            Screen::CatchAllSettings => print_catch_all(&bank.catch_all),
+            Screen::Exercises => print_exercises(bank),
+            Screen::Responses => print_common_responses(bank),
+            Screen::Stats => print_stats(bank),
+        }
+    }
+
+    fn handle(&self, bank: &mut Bank, input: &str) -> Nav {
+        match *self {
+            Screen::Bank => match input {
+                "a" => {
+                    let sentence = get_sentence();
+                    print_key_help(&[("a", "Return to menu"), ("p", "Process this one")]);
+                    match read_input().as_str() {
+                        "a" => {
+                            bank.add(sentence);
+                            announce(&format!("Question {} added.", bank.questions.len()));
+                        }
+                        "p" => {
+                            bank.add(sentence);
+                            announce(&format!("Question {} added.", bank.questions.len()));
+                            return Nav::Push(Screen::EditSentence(bank.questions.len() - 1));
+                        }
+                        _ => announce("Question discarded."),
+                    }
+                    Nav::Stay
+                }
+                "e" => match choose_index("Enter no.: ", bank.questions.len()) {
+                    Some(q) => Nav::Push(Screen::EditSentence(q)),
+                    None => Nav::Stay,
+                },
+                "d" => {
+                    if let Some(q) = choose_index("Enter no.: ", bank.questions.len()) {
+                        let question = &bank.questions[q].initial_sentence;
+                        if confirm(&format!("Delete question {}, {}?", q + 1, question)) {
+                            bank.questions.remove(q);
+                            announce(&format!("Question {} deleted.", q + 1));
+                        }
+                    }
+                    Nav::Stay
+                }
+                "m" => match choose_index("Enter no.: ", bank.questions.len()) {
+                    Some(q) => Nav::Push(Screen::MoveQuestion(q)),
+                    None => Nav::Stay,
+                },
+                "p" => {
+                    print_to_file(bank);
+                    Nav::Stay
+                }
+                "g" => {
+                    print_gift_file(bank);
+                    Nav::Stay
+                }
+                "i" => {
+                    import_gift_file(bank);
+                    Nav::Stay
+                }
+                "o" => Nav::Push(Screen::BankDefaults),
+                "x" => Nav::Push(Screen::Exercises),
+                "r" => {
+                    practise(bank);
+                    Nav::Stay
+                }
+                "v" => {
+                    run_test_cases(bank);
+                    Nav::Stay
+                }
+                "l" => {
+                    lint(bank);
+                    Nav::Stay
+                }
+                "w" => Nav::Push(Screen::Responses),
+                "y" => Nav::Push(Screen::Stats),
+                "t" => {
+                    if set_default_gap_type(bank) {
+                        announce(&format!(
+                            "Default gap type set to {}.",
+                            bank.default_gap_type.label()
+                        ));
+                    }
+                    Nav::Stay
+                }
+                "s" => {
+                    let count = bank.questions.len();
+                    if confirm(&format!("Remove all {} questions from the bank?", count)) {
+                        bank.questions.clear();
+                        announce("All questions removed.");
+                    }
+                    Nav::Stay
+                }
+                "q" => Nav::Quit,
+                _ => Nav::Stay,
+            },
+            Screen::MoveQuestion(idx) => match input {
+                "u" if idx > 0 => {
+                    bank.questions[idx - 1..idx + 1].rotate_right(1);
+                    announce(&format!("Question moved to position {}.", idx));
+                    Nav::Replace(Screen::MoveQuestion(idx - 1))
+                }
+                "d" if idx + 1 < bank.questions.len() => {
+                    bank.questions[idx..idx + 2].rotate_left(1);
+                    announce(&format!("Question moved to position {}.", idx + 2));
+                    Nav::Replace(Screen::MoveQuestion(idx + 1))
+                }
+                "a" => Nav::Back,
+                _ => Nav::Stay,
+            },
+            Screen::EditSentence(q) => match input {
+                "j" => Nav::Push(Screen::JoinParts(q)),
+                "r" => Nav::Push(Screen::ReorderParts(q)),
+                "a" => {
+                    prepare_answers(&mut bank.questions[q]);
+                    Nav::Replace(Screen::SetAnswers(q))
+                }
+                _ => Nav::Stay,
+            },
+            Screen::JoinParts(q) => match input {
+                "a" => Nav::Back,
+                "r" => {
+                    let note = revert_splits(&mut bank.questions[q]);
+                    report_chunk_edit(Ok(note), "Last change reverted.");
+                    Nav::Stay
+                }
+                _ => {
+                    if let Ok(num) = input.parse::<usize>() {
+                        if num > 0 {
+                            report_chunk_edit(
+                                apply_join(&mut bank.questions[q], num - 1),
+                                &format!("Word {} joined with the next word.", num),
+                            );
+                        }
+                    }
+                    Nav::Stay
+                }
+            },
+            Screen::ReorderParts(q) => {
+                match input {
+                    "a" => return Nav::Back,
+                    "r" => {
+                        let note = revert_splits(&mut bank.questions[q]);
+                        report_chunk_edit(Ok(note), "Last change reverted.");
+                    }
+                    "u" => report_chunk_edit(
+                        move_up(&mut bank.questions[q], get_num_choice("Which word? ")),
+                        "Word order changed.",
+                    ),
+                    "d" => report_chunk_edit(
+                        apply_reorder(&mut bank.questions[q], get_num_choice("Which word? ")),
+                        "Word order changed.",
+                    ),
+                    _ => {}
+                }
+                Nav::Stay
+            }
+            Screen::SetAnswers(q) => match input {
+                "c" if check_for_complete(&mut bank.questions[q]) => {
+                    announce(&format!("Question {} marked complete.", q + 1));
+                    Nav::Back
+                }
+                "m" => Nav::Home,
+                "o" => Nav::Push(Screen::QuestionSettings(q)),
+                "v" => Nav::Push(Screen::TestCases(q)),
+                "e" => {
+                    let question = &bank.questions[q];
+                    let chunks = question.splits[question.current_split].len();
+                    match choose_index("Which no.? ", chunks.min(question.answers.len())) {
+                        Some(c) => Nav::Push(Screen::AnswerOptions(q, c)),
+                        None => Nav::Stay,
+                    }
+                }
+                _ => Nav::Stay,
+            },
+            Screen::AnswerOptions(q, c) => match input {
+                "a" => {
+                    add_answer(&mut bank.questions[q], c);
+                    announce("Answer added.");
+                    Nav::Stay
+                }
+                "e" => Nav::Push(Screen::EditAnswers(q, c)),
+                "d" => Nav::Push(Screen::DeleteAnswers(q, c)),
+                "c" => {
+                    if edit_cloze_code(&mut bank.questions[q], c, bank.default_gap_type) {
+                        announce("Answers replaced from the cloze code.");
+                    }
+                    Nav::Stay
+                }
+                "t" => {
+                    if set_gap_type(&mut bank.questions[q], c) {
+                        announce("Gap type changed.");
+                    }
+                    Nav::Stay
+                }
+                "w" => {
+                    if set_gap_weight(&mut bank.questions[q], c) {
+                        announce("Weight changed.");
+                    }
+                    Nav::Stay
+                }
+                "k" => {
+                    if set_category(&mut bank.questions[q], c) {
+                        announce("Grammar category changed.");
+                    }
+                    Nav::Stay
+                }
+                "m" => match choose_index("Which no.? ", bank.questions[q].answers[c].len()) {
+                    Some(opt) => Nav::Push(Screen::MoveAnswer(q, c, opt)),
+                    None => Nav::Stay,
+                },
+                "s" => {
+                    sort_answers(&mut bank.questions[q], c);
+                    announce("Answers sorted.");
+                    Nav::Stay
+                }
+                "f" => {
+                    if reorder_specific_first(&mut bank.questions[q], c, bank.default_gap_type) {
+                        announce("Answers reordered.");
+                    } else {
+                        announce("The answers are already in order.");
+                    }
+                    Nav::Stay
+                }
+                "" => Nav::Back,
+                _ => Nav::Stay,
+            },
+            Screen::EditAnswers(q, c) => {
+                let options = bank.questions[q].answers[c].len();
+                match input {
+                    "d" => return Nav::Push(Screen::DeleteAnswers(q, c)),
+                    "a" => return Nav::Back,
+                    "m" => {
+                        if let Some(opt) = choose_index("Which no.? ", options) {
+                            mark_non_question(&mut bank.questions[q], c, opt);
+                            announce(&format!("Answer {} marked as fixed text.", opt + 1));
+                        }
+                    }
+                    "" => {}
+                    _ => {
+                        let opt = match parse_index(input, options) {
+                            Some(opt) => Some(opt),
+                            None => choose_index("Which no.? ", options),
+                        };
+                        if let Some(opt) = opt {
+                            edit_answer(&mut bank.questions[q], c, opt);
+                            announce(&format!("Answer {} updated.", opt + 1));
+                        }
+                    }
+                }
+                Nav::Stay
+            }
+            Screen::DeleteAnswers(q, c) => match input {
+                "" => Nav::Back,
+                _ => {
+                    if let Some(opt) = parse_index(input, bank.questions[q].answers[c].len()) {
+                        delete_answer(&mut bank.questions[q], c, opt);
+                        announce(&format!("Answer {} deleted.", opt + 1));
+                    }
+                    Nav::Stay
+                }
+            },
+            Screen::MoveAnswer(q, c, opt) => {
+                let options = bank.questions[q].answers[c].len();
+                let to = match input {
+                    "a" => return Nav::Back,
+                    "u" if opt > 0 => opt - 1,
+                    "d" if opt + 1 < options => opt + 1,
+                    _ => match parse_index(input, options) {
+                        Some(to) => to,
+                        None => return Nav::Stay,
+                    },
+                };
+                move_answer(&mut bank.questions[q], c, opt, to);
+                announce(&format!("Answer moved to position {}.", to + 1));
+                Nav::Replace(Screen::MoveAnswer(q, c, to))
+            }
+            Screen::QuestionSettings(q) => match input {
+                "" => Nav::Back,
+                _ => {
+                    if let Some(field) = parse_index(input, FIELDS.len()) {
+                        change_setting(&mut bank.questions[q].metadata, field);
+                    }
+                    Nav::Stay
+                }
+            },
+            Screen::TestCases(q) => {
+                match input {
+                    "" => return Nav::Back,
+                    "a" if add_test_case(bank, q) => {
+                        let added = bank.questions[q].test_cases.len();
+                        announce(&format!("Test case {} added.", added));
+                    }
+                    "d" => {
+                        let question = &mut bank.questions[q];
+                        if let Some(idx) = choose_index("Which test? ", question.test_cases.len()) {
+                            question.test_cases.remove(idx);
+                            announce(&format!("Test case {} deleted.", idx + 1));
+                        }
+                    }
+                    _ => {}
+                }
+                Nav::Stay
+            }
+            Screen::BankDefaults => match input {
+                "" => Nav::Back,
+                "k" => Nav::Push(Screen::CatchAllSettings),
+                _ => {
+                    match parse_index(input, FIELDS.len()) {
+                        Some(field) if QUESTION_ONLY.contains(&field) => {
+                            print_boxed("That is set per question.\nContinue: c");
+                            read_input();
+                        }
+                        Some(field) => change_setting(&mut bank.defaults, field),
+                        None => {}
+                    }
+                    Nav::Stay
+                }
+            },
+            Screen::CatchAllSettings => match input {
+                "" => Nav::Back,
+                _ => {
+                    if let Some(field) = parse_index(input, CATCH_ALL_FIELDS.len()) {
+                        change_catch_all(&mut bank.catch_all, field);
+                    }
+                    Nav::Stay
+                }
+            },
+            Screen::Exercises => {
+                match input {
+                    "" => return Nav::Back,
+                    "a" => {
+                        add_exercise(bank);
+                        announce(&format!("Exercise {} added.", bank.exercises.len()));
+                    }
+                    "d" => {
+                        if let Some(idx) = choose_index("Which exercise? ", bank.exercises.len()) {
+                            delete_exercise(bank, idx);
+                            announce(&format!("Exercise {} deleted.", idx + 1));
+                        }
+                    }
+                    "u" => {
+                        if let Some(idx) = choose_index("Which exercise? ", bank.exercises.len()) {
+                            let count = assign_exercise(bank, idx);
+                            announce(&format!(
+                                "{} questions moved to exercise {}.",
+                                count,
+                                idx + 1
+                            ));
+                        }
+                    }
+                    _ => {}
+                }
+                Nav::Stay
+            }
+            Screen::Responses => {
+                match input {
+                    "" => return Nav::Back,
+                    "i" => import_responses(bank),
+                    "d" => {
+                        if let Some(idx) = choose_index("Which no.? ", bank.common_responses.len())
+                        {
+                            bank.common_responses.remove(idx);
+                            announce(&format!("Response {} dismissed.", idx + 1));
+                        }
+                    }
+                    "c" => {
+                        bank.common_responses.clear();
+                        announce("Responses cleared.");
+                    }
+                    _ => {
+                        if let Some(idx) = parse_index(input, bank.common_responses.len()) {
+                            let common = bank.common_responses[idx].clone();
+                            if let Some((q, c)) = response_target(bank, &common) {
+                                add_response_answer(&mut bank.questions[q], c, &common.response);
+                                bank.common_responses.remove(idx);
+                                announce(&format!("Answer added to question {}.", q + 1));
+                            }
+                        }
+                    }
+                }
+                Nav::Stay
+            }
+            Screen::Stats => {
+                match input {
+                    "" => return Nav::Back,
+                    "i" => import_stats(bank),
+                    "c" => clear_stats(bank),
+                    _ => {}
+                }
+                Nav::Stay
+            }
+        }
+    }
+}
+
+fn change_setting(metadata: &mut Metadata, field: usize) {
+    println!("{} (blank for the default):", FIELDS[field]);
+    let current = metadata.show(field).unwrap_or_default();
+    let line = read_input_with_initial(&current, Vec::new());
+    match metadata.set(field, &line) {
+        Ok(()) => announce(&format!("{} changed.", FIELDS[field])),
+        Err(problem) => {
+            print_boxed(&format!("{}\nContinue: c", problem));
+            read_input();
+        }
+    }
+}
+
+fn change_catch_all(catch_all: &mut CatchAll, field: usize) {
+    println!("{}:", CATCH_ALL_FIELDS[field]);
+    let line = read_input_with_initial(&catch_all.show(field), Vec::new());
+    match catch_all.set(field, &line) {
+        Ok(()) => announce(&format!("{} changed.", CATCH_ALL_FIELDS[field])),
+        Err(problem) => {
+            print_boxed(&format!("{}\nContinue: c", problem));
+            read_input();
+        }
+    }
+}
+
+fn parse_index(input: &str, len: usize) -> Option<usize> {
+    match input.parse::<usize>() {
+        Ok(num) if num > 0 && num <= len => Some(num - 1),
+        _ => None,
+    }
+}
+
+// The bank is saved as soon as it changes, so anything that throws
+// work away is asked about first.
+fn confirm(question: &str) -> bool {
+    print_boxed(question);
+    print_key_help(&[("y", "Yes"), ("n", "No")]);
+    read_input().trim() == "y"
+}
+
+fn choose_index(prompt: &str, len: usize) -> Option<usize> {
+    let idx = get_num_choice(prompt);
+    if idx < len {
+        Some(idx)
+    } else {
+        println!("That number is too high!");
+        None
+    }
+}
+
+// Answers lost or set aside by a chunk edit are worth stopping for;
+// anything else is just announced.
+fn report_chunk_edit(result: Result<Option<String>, &str>, done: &str) {
+    match result {
+        Err(problem) => println!("{}", problem),
+        Ok(None) => announce(done),
+        Ok(Some(note)) => {
+            print_boxed(&format!("{}\n{}\nContinue: c", done, note));
+            read_input();
+        }
+    }
+}
+
+pub fn run_screens(bank: &mut Bank) {
+    let mut autosave = AutoSave::new(bank, &config().bank_path);
+    let mut stack: Vec<Screen> = vec![Screen::Bank];
+    while let Some(screen) = stack.last() {
+        clear_screen();
+        let crumbs: Vec<String> = stack.iter().map(Screen::crumb).collect();
+        print_breadcrumbs(&crumbs);
+        print_boxed(screen.title());
+        screen.render(bank);
+        let mut keys = screen.keys();
+        if stack.len() > 1 {
+            keys.push(("b", "Back"));
+        }
+        print_key_help(&keys);
+
+        let input = read_input();
+        if input_closed() {
+            break;
+        }
+        let nav = match input.trim() {
+            "b" if stack.len() > 1 => Nav::Back,
+            entry => screen.handle(bank, entry),
+        };
+        if let Some(problem) = autosave.save(bank) {
+            print_boxed(&format!("{}\nContinue: c", problem));
+            read_input();
+        }
+        if input_closed() {
+            break;
+        }
+        match nav {
+            Nav::Stay => {}
+            Nav::Push(next) => stack.push(next),
+            Nav::Replace(next) => {
+                stack.pop();
+                stack.push(next);
+            }
+            Nav::Back => {
+                stack.pop();
+            }
+            Nav::Home => stack.truncate(1),
+            Nav::Quit => break,
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::grading::{grade_question, locate_gap};
+use crate::printing::{announce, print_boxed};
+use crate::responses::read_report;
+use crate::{read_input, Bank, Sentence};
+use serde::{Deserialize, Serialize};
+
+// How students did on a question, from the responses reports loaded so
+// far. Scores are sums of fractions, so the facility index is the score
+// over the attempts.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuestionStats {
+    pub question: u32,
+    pub attempts: u32,
+    pub score: f32,
+    pub gaps: Vec<GapStats>,
+    pub feedback: Vec<(String, u32)>,
+}
+
+// The chunk's text is kept to find the gap again after the chunks
+// change.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GapStats {
+    pub gap: usize,
+    #[serde(default)]
+    pub chunk: String,
+    pub attempts: u32,
+    pub score: f32,
+}
+
+fn facility(score: f32, attempts: u32) -> f32 {
+    if attempts == 0 {
+        0.0
+    } else {
+        score / attempts as f32 * 100.0
+    }
+}
+
+impl QuestionStats {
+    pub fn facility(&self) -> f32 {
+        facility(self.score, self.attempts)
+    }
+
+    // The figures for the chunk now at `gap`, if it is still a gap.
+    pub fn gap(&self, sentence: &Sentence, gap: usize) -> Option<&GapStats> {
+        self.gaps
+            .iter()
+            .find(|stats| stats.current_gap(sentence) == Some(gap))
+    }
+}
+
+impl GapStats {
+    pub fn facility(&self) -> f32 {
+        facility(self.score, self.attempts)
+    }
+
+    pub fn current_gap(&self, sentence: &Sentence) -> Option<usize> {
+        locate_gap(sentence, self.gap, &self.chunk)
+    }
+}
+
+pub fn question_stats(bank: &Bank, id: u32) -> Option<&QuestionStats> {
+    bank.stats.iter().find(|stats| stats.question == id)
+}
+
+// Grades each response as Moodle would with the answers as they stand.
+// Moodle's reports are cumulative, so the figures for every question in
+// the report replace what earlier reports gave rather than adding to it.
+pub fn import_stats(bank: &mut Bank) {
+    print!("Which file? ");
+    let path = read_input();
+    let report = match read_report(bank, path.trim()) {
+        Ok(report) => report,
+        Err(problem) => {
+            print_boxed(&format!("{}\nContinue: c", problem));
+            read_input();
+            return;
+        }
+    };
+    let mut stats = std::mem::take(&mut bank.stats);
+    stats.retain(|entry| {
+        !report
+            .responses
+            .iter()
+            .any(|student| bank.questions[student.question].id == entry.question)
+    });
+    for student in report.responses.iter() {
+        let question = &bank.questions[student.question];
+        let parts: Vec<&str> = student.parts.iter().map(String::as_str).collect();
+        let grade = grade_question(bank, question, &parts);
+        let pos = match stats.iter().position(|s| s.question == question.id) {
+            Some(pos) => pos,
+            None => {
+                stats.push(QuestionStats {
+                    question: question.id,
+                    attempts: 0,
+                    score: 0.0,
+                    gaps: Vec::new(),
+                    feedback: Vec::new(),
+                });
+                stats.len() - 1
+            }
+        };
+        let entry = &mut stats[pos];
+        entry.attempts += 1;
+        entry.score += grade.fraction;
+        for gap_grade in grade.gaps.iter() {
+            match entry.gaps.iter_mut().find(|s| s.gap == gap_grade.gap) {
+                Some(gap) => {
+                    gap.attempts += 1;
+                    gap.score += gap_grade.fraction;
+                }
+                None => entry.gaps.push(GapStats {
+                    gap: gap_grade.gap,
+                    chunk: question.splits[question.current_split][gap_grade.gap].clone(),
+                    attempts: 1,
+                    score: gap_grade.fraction,
+                }),
+            }
+            let feedback = gap_grade.feedback.as_str();
+            if feedback.is_empty() {
+                continue;
+            }
+            match entry.feedback.iter_mut().find(|(text, _)| text == feedback) {
+                Some((_, count)) => *count += 1,
+                None => entry.feedback.push((feedback.to_string(), 1)),
+            }
+        }
+    }
+    bank.stats = stats;
+    let mut summary = format!("Read {} responses.", report.responses.len());
+    if report.unmatched > 0 {
+        summary.push_str(&format!(
+            "\n{} responses were to questions not in this bank.",
+            report.unmatched
+        ));
+    }
+    print_boxed(&format!("{}\nContinue: c", summary));
+    read_input();
+}
+
+pub fn clear_stats(bank: &mut Bank) {
+    bank.stats.clear();
+    announce("Statistics cleared.");
+}
+
+// Questions hardest first, then the ten hardest gaps and the feedback
+// students see most.
+pub fn print_stats(bank: &Bank) {
+    let mut questions: Vec<(usize, &QuestionStats)> = bank
+        .questions
+        .iter()
+        .enumerate()
+        .filter_map(|(num, question)| Some((num, question_stats(bank, question.id)?)))
+        .collect();
+    if questions.is_empty() {
+        println!("No statistics yet. Import a Moodle responses report first.");
+        println!();
+        return;
+    }
+    questions.sort_by(|a, b| a.1.facility().total_cmp(&b.1.facility()));
+    println!(
+        "Facility comes from grading the responses against the answers as they were at import."
+    );
+    println!("Questions:");
+    for (num, stats) in questions.iter() {
+        println!(
+            "  Question {}: facility {:.0}% from {} responses",
+            num + 1,
+            stats.facility(),
+            stats.attempts
+        );
+    }
+
+    let mut hardest: Vec<(usize, usize, &GapStats)> = Vec::new();
+    for (num, stats) in questions.iter() {
+        let question = &bank.questions[*num];
+        for gap in stats.gaps.iter() {
+            if let Some(idx) = gap.current_gap(question) {
+                hardest.push((*num, idx, gap));
+            }
+        }
+    }
+    hardest.sort_by(|a, b| a.2.facility().total_cmp(&b.2.facility()));
+    println!("Hardest gaps:");
+    for (num, idx, gap) in hardest.iter().take(10) {
+        let question = &bank.questions[*num];
+        println!(
+            "  Question {}, {}: facility {:.0}%",
+            num + 1,
+            question.splits[question.current_split][*idx],
+            gap.facility()
+        );
+    }
+
+    let mut feedback: Vec<(&str, u32)> = Vec::new();
+    for (_, stats) in questions.iter() {
+        for (text, count) in stats.feedback.iter() {
+            match feedback.iter_mut().find(|(seen, _)| seen == text) {
+                Some((_, total)) => *total += count,
+                None => feedback.push((text, *count)),
+            }
+        }
+    }
+    feedback.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    println!("Most shown feedback:");
+    for (text, count) in feedback.iter().take(10) {
+        println!("  {} x{}", text, count);
+    }
+    println!();
+}
+
+pub fn print_gap_stats(bank: &Bank, q: usize, c: usize) {
+    let question = &bank.questions[q];
+    let Some(stats) = question_stats(bank, question.id) else {
+        return;
+    };
+    if let Some(gap) = stats.gap(question, c) {
+        println!(
+            "Facility: {:.0}% from {} responses, graded against the answers at import",
+            gap.facility(),
+            gap.attempts
+        );
+        println!();
+    }
+}
@@ -0,0 +1,62 @@
+use crate::printing::announce;
+use crate::Bank;
+use std::error::Error;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+// The bank is kept between sessions as JSON; a missing file just means
+// starting with an empty bank.
+pub fn load_bank(path: &Path) -> Result<Bank, Box<dyn Error>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|error| format!("Unable to read {}: {}", path.display(), error).into()),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(Bank::new()),
+        Err(error) => Err(format!("Unable to read {}: {}", path.display(), error).into()),
+    }
+}
+
+pub fn save_bank(bank: &Bank, path: &Path) -> Result<(), Box<dyn Error>> {
+    let contents = serde_json::to_string_pretty(bank)?;
+    fs::write(path, contents)?;
+    announce(&format!("Bank saved to {}.", path.display()));
+    Ok(())
+}
+
+// Writes the bank whenever it has changed since the last write, so that
+// work survives the program stopping without a proper quit. A failure
+// is passed back once, for the caller to show however suits its screen.
+pub struct AutoSave {
+    path: PathBuf,
+    written: String,
+    failing: bool,
+}
+
+impl AutoSave {
+    pub fn new(bank: &Bank, path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            written: serde_json::to_string_pretty(bank).unwrap_or_default(),
+            failing: false,
+        }
+    }
+
+    pub fn save(&mut self, bank: &Bank) -> Option<String> {
+        let contents = serde_json::to_string_pretty(bank).ok()?;
+        if contents == self.written {
+            return None;
+        }
+        match fs::write(&self.path, &contents) {
+            Ok(()) => {
+                self.written = contents;
+                self.failing = false;
+                None
+            }
+            Err(error) if !self.failing => {
+                self.failing = true;
+                Some(format!("Unable to save {}: {}", self.path.display(), error))
+            }
+            Err(_) => None,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::config::config;
+use std::fs;
+
+// Placeholders are written `{name}`: english, cloze, end, stage,
+// exercise, number and id. `end` is the closing full stop for sentences
+// that have no punctuation of their own, `number` counts from 1001
+// within the exercise and `id` is the question's permanent ID. Anything
+// else in braces is left as it is.
+pub struct Templates {
+    pub question: String,
+    pub category: String,
+    pub name: String,
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Self {
+            question: "<p>{english}</p>\n\
+                 <p><font size=\"4\" face=\"times new roman,times,serif\">{cloze}{end}</font></p>"
+                .to_string(),
+            category: "$course$/{stage}/Composition/{exercise}".to_string(),
+            name: "{exercise}_q_{id}".to_string(),
+        }
+    }
+}
+
+impl Templates {
+    // Takes a `template.<kind>` config line. A value starting with `@`
+    // names a file to read the template from, and `\n` in a value is a
+    // line break.
+    pub fn set(&mut self, kind: &str, value: &str) {
+        let template = match value.strip_prefix('@') {
+            Some(path) => match fs::read_to_string(path.trim()) {
+                Ok(contents) => contents.trim_end().to_string(),
+                Err(_) => return,
+            },
+            None => value.replace("\\n", "\n"),
+        };
+        match kind {
+            "question" => self.question = template,
+            "category" => self.category = template,
+            "name" => self.name = template,
+            _ => {}
+        }
+    }
+}
+
+// Values are inserted as they are; braces inside them (cloze codes,
+// say) are never read as placeholders.
+pub fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after.find('}').and_then(|close| {
+            values
+                .iter()
+                .find(|(key, _)| *key == &after[..close])
+                .map(|(_, value)| (close, value))
+        });
+        match value {
+            Some((close, value)) => {
+                filled.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+pub fn category_path(stage: &str, exercise: &str) -> String {
+    fill(
+        &config().templates.category,
+        &[("stage", stage), ("exercise", exercise)],
+    )
+}
+
+pub fn question_name(stage: &str, exercise: &str, number: u32, id: u32) -> String {
+    fill(
+        &config().templates.name,
+        &[
+            ("stage", stage),
+            ("exercise", exercise),
+            ("number", &number.to_string()),
+            ("id", &id.to_string()),
+        ],
+    )
+}
+
+pub fn question_text(
+    english: &str,
+    cloze: &str,
+    end: &str,
+    stage: &str,
+    exercise: &str,
+    number: u32,
+    id: u32,
+) -> String {
+    fill(
+        &config().templates.question,
+        &[
+            ("english", english),
+            ("cloze", cloze),
+            ("end", end),
+            ("stage", stage),
+            ("exercise", exercise),
+            ("number", &number.to_string()),
+            ("id", &id.to_string()),
+        ],
+    )
+}
@@ -0,0 +1,159 @@
+use crate::grading::{gaps, grade_gap};
+use crate::printing::{print_boxed, print_key_help};
+use crate::theme::{paint, Role};
+use crate::{read_input, Bank, ExpectedGrade, Sentence, TestCase};
+
+fn mark_of(fraction: f32) -> u8 {
+    (fraction * 100.0).round() as u8
+}
+
+// Describes every way the question's current answers grade the test
+// case differently from what it expects.
+pub fn check_case(bank: &Bank, question: &Sentence, case: &TestCase) -> Vec<String> {
+    let gaps = gaps(question);
+    if gaps.len() != case.gaps.len() {
+        return vec![format!(
+            "The question has {} gaps but the test case has responses for {}.",
+            gaps.len(),
+            case.gaps.len()
+        )];
+    }
+    let mut problems = Vec::new();
+    for (gap, expected) in gaps.into_iter().zip(&case.gaps) {
+        let grade = grade_gap(bank, question, gap, &expected.response);
+        let mark = mark_of(grade.fraction);
+        if mark != expected.mark || grade.feedback != expected.feedback {
+            problems.push(format!(
+                "\"{}\" for {}: expected {}% \"{}\", got {}% \"{}\"",
+                expected.response,
+                question.splits[question.current_split][gap],
+                expected.mark,
+                expected.feedback,
+                mark,
+                grade.feedback
+            ));
+        }
+    }
+    problems
+}
+
+// One line per failing test case, naming the question by its number in
+// the bank.
+fn failures(bank: &Bank, ids: &[u32]) -> (usize, Vec<String>) {
+    let mut run = 0;
+    let mut lines = Vec::new();
+    for (num, question) in bank.questions.iter().enumerate() {
+        if !ids.contains(&question.id) {
+            continue;
+        }
+        for (case_num, case) in question.test_cases.iter().enumerate() {
+            run += 1;
+            for problem in check_case(bank, question, case) {
+                lines.push(format!(
+                    "Question {}, test {}: {}",
+                    num + 1,
+                    case_num + 1,
+                    problem
+                ));
+            }
+        }
+    }
+    (run, lines)
+}
+
+pub fn run_test_cases(bank: &Bank) {
+    let ids: Vec<u32> = bank.questions.iter().map(|question| question.id).collect();
+    let (run, mut lines) = failures(bank, &ids);
+    if run == 0 {
+        lines.push("No questions have test cases yet.".to_string());
+    } else if lines.is_empty() {
+        lines.push(format!("All {} test cases pass.", run));
+    }
+    lines.push("Continue: c".to_string());
+    print_boxed(&lines.join("\n"));
+    read_input();
+}
+
+// Returns whether to go ahead with exporting the questions.
+pub fn check_before_export(bank: &Bank, ids: &[u32]) -> bool {
+    let (_, mut lines) = failures(bank, ids);
+    if lines.is_empty() {
+        return true;
+    }
+    lines.insert(0, "These questions now grade differently:".to_string());
+    print_boxed(&lines.join("\n"));
+    print_key_help(&[("y", "Export anyway"), ("n", "Cancel")]);
+    read_input() == "y"
+}
+
+pub fn print_test_cases(bank: &Bank, question: &Sentence) {
+    if question.test_cases.is_empty() {
+        println!("No test cases yet.");
+    }
+    for (num, case) in question.test_cases.iter().enumerate() {
+        let problems = check_case(bank, question, case);
+        let responses: Vec<&str> = case
+            .gaps
+            .iter()
+            .map(|expected| expected.response.as_str())
+            .collect();
+        let (role, result) = if problems.is_empty() {
+            (Role::FullMark, "pass")
+        } else {
+            (Role::ZeroMark, "FAIL")
+        };
+        println!(
+            "{}. {} [{}]",
+            num + 1,
+            responses.join(" | "),
+            paint(role, result)
+        );
+        for problem in problems {
+            println!("   {}", problem);
+        }
+    }
+    println!();
+}
+
+// Asks for a response to each gap and, unless told otherwise, expects
+// the mark and feedback it gets now.
+pub fn add_test_case(bank: &mut Bank, q: usize) -> bool {
+    let question = &bank.questions[q];
+    let mut case = TestCase { gaps: Vec::new() };
+    for gap in gaps(question) {
+        println!(
+            "Response for {}:",
+            question.splits[question.current_split][gap]
+        );
+        let response = read_input();
+        let grade = grade_gap(bank, question, gap, &response);
+        let current = mark_of(grade.fraction);
+        println!("Expected mark (blank for {}):", current);
+        let mark = match read_input().trim() {
+            "" => current,
+            text => match text.parse::<u8>() {
+                Ok(mark) if mark <= 100 => mark,
+                _ => {
+                    print_boxed("The mark must be a number from 0 to 100.\nContinue: c");
+                    read_input();
+                    return false;
+                }
+            },
+        };
+        println!("Expected feedback (blank for \"{}\"):", grade.feedback);
+        let feedback = match read_input().trim() {
+            "" => grade.feedback,
+            text => text.to_string(),
+        };
+        case.gaps.push(ExpectedGrade {
+            response,
+            mark,
+            feedback,
+        });
+    }
+    if case.gaps.is_empty() {
+        return false;
+    }
+    bank.questions[q].test_cases.push(case);
+    true
+}
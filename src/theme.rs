@@ -0,0 +1,75 @@
+use crate::config::config;
+
+#[derive(Clone, Copy)]
+pub enum Role {
+    Done,
+    Incomplete,
+    FullMark,
+    PartMark,
+    ZeroMark,
+    Fixed,
+    Syntax,
+    Separator,
+    Feedback,
+}
+
+const ROLE_NAMES: [(&str, Role); 9] = [
+    ("done", Role::Done),
+    ("incomplete", Role::Incomplete),
+    ("full_mark", Role::FullMark),
+    ("part_mark", Role::PartMark),
+    ("zero_mark", Role::ZeroMark),
+    ("fixed", Role::Fixed),
+    ("syntax", Role::Syntax),
+    ("separator", Role::Separator),
+    ("feedback", Role::Feedback),
+];
+
+// Each entry is an SGR parameter string, e.g. "1;32" for bold green.
+pub struct Theme {
+    codes: [String; 9],
+}
+
+impl Theme {
+    pub fn named(name: &str) -> Option<Self> {
+        let codes: [&str; 9] = match name {
+            "default" => ["32", "33", "32", "33", "31", "2", "36", "35", "34"],
+            "high-contrast" => [
+                "1;92", "1;93", "1;92", "1;93", "1;91", "2", "1;96", "1;95", "1;94",
+            ],
+            "mono" => ["1", "", "1", "4", "", "2", "1", "1", "3"],
+            _ => return None,
+        };
+        Some(Self {
+            codes: codes.map(String::from),
+        })
+    }
+
+    // Overrides one role from a `theme.<role> = <sgr>` config line.
+    pub fn set(&mut self, role: &str, code: &str) {
+        if let Some((_, role)) = ROLE_NAMES.iter().find(|(name, _)| *name == role) {
+            self.codes[*role as usize] = code.to_string();
+        }
+    }
+
+    fn code(&self, role: Role) -> &str {
+        &self.codes[role as usize]
+    }
+}
+
+pub fn mark_role(mark: u8) -> Role {
+    match mark {
+        100 => Role::FullMark,
+        0 => Role::ZeroMark,
+        _ => Role::PartMark,
+    }
+}
+
+pub fn paint(role: Role, text: &str) -> String {
+    let config = config();
+    let code = config.theme.code(role);
+    if !config.color || code.is_empty() || text.is_empty() {
+        return text.to_string();
+    }
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
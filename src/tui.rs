@@ -0,0 +1,538 @@
+use crate::catchall::catch_all_option;
+use crate::cloze::ClozeType;
+use crate::config::config;
+use crate::printing::cloze_preview;
+use crate::storage::AutoSave;
+use crate::{
+    apply_join, apply_reorder, can_complete, delete_answer, mark_non_question, move_up,
+    prepare_answers, revert_splits, AnswerOption, Bank, Category, Sentence,
+};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::mem;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Pane {
+    Bank,
+    Chunks,
+    Options,
+}
+
+// What the text typed into the footer prompt will be used for.
+enum Prompt {
+    NewQuestion,
+    Answer,
+    Mark(String),
+    Feedback(String, u8),
+    EditAnswer(usize),
+    EditMark(usize),
+    EditFeedback(usize),
+    Weight,
+}
+
+impl Prompt {
+    fn label(&self) -> &'static str {
+        match self {
+            Prompt::NewQuestion => "New sentence",
+            Prompt::Answer | Prompt::EditAnswer(_) => "Answer",
+            Prompt::Mark(_) | Prompt::EditMark(_) => "Mark (0-100)",
+            Prompt::Feedback(..) | Prompt::EditFeedback(_) => "Feedback",
+            Prompt::Weight => "Weight (blank for default)",
+        }
+    }
+}
+
+struct App {
+    pane: Pane,
+    question: usize,
+    chunk: usize,
+    option: usize,
+    prompt: Option<(Prompt, String)>,
+    message: String,
+    // Set by a first `d`; only a second `d` straight after deletes.
+    confirm_delete: bool,
+}
+
+enum Action {
+    Continue,
+    Quit,
+}
+
+pub fn run_tui(bank: &mut Bank) -> io::Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, bank);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, bank: &mut Bank) -> io::Result<()> {
+    let mut app = App {
+        pane: Pane::Bank,
+        question: 0,
+        chunk: 0,
+        option: 0,
+        prompt: None,
+        message: String::new(),
+        confirm_delete: false,
+    };
+    let mut autosave = AutoSave::new(bank, &config().bank_path);
+    loop {
+        terminal.draw(|frame| draw(frame, &app, bank))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let action = if app.prompt.is_some() {
+                handle_prompt(&mut app, bank, key.code);
+                Action::Continue
+            } else {
+                app.message.clear();
+                handle_key(&mut app, bank, key.code)
+            };
+            if let Some(problem) = autosave.save(bank) {
+                app.message = problem;
+            }
+            if let Action::Quit = action {
+                return Ok(());
+            }
+            clamp_selection(&mut app, &bank.questions);
+        }
+    }
+}
+
+fn chunk_edit_message(result: Result<Option<String>, &str>, done: &str) -> String {
+    match result {
+        Err(problem) => problem.to_string(),
+        Ok(None) => done.to_string(),
+        Ok(Some(note)) => format!("{} {}", done, note),
+    }
+}
+
+fn chunk_count(bank: &[Sentence], app: &App) -> usize {
+    bank.get(app.question)
+        .map_or(0, |sentence| sentence.splits[sentence.current_split].len())
+}
+
+fn option_count(bank: &[Sentence], app: &App) -> usize {
+    bank.get(app.question)
+        .and_then(|sentence| sentence.answers.get(app.chunk))
+        .map_or(0, Vec::len)
+}
+
+fn clamp_selection(app: &mut App, bank: &[Sentence]) {
+    app.question = app.question.min(bank.len().saturating_sub(1));
+    app.chunk = app.chunk.min(chunk_count(bank, app).saturating_sub(1));
+    app.option = app.option.min(option_count(bank, app).saturating_sub(1));
+    if bank.is_empty() {
+        app.pane = Pane::Bank;
+    }
+}
+
+// Steps through every type and back round to the bank default.
+fn next_gap_type(current: Option<ClozeType>) -> Option<ClozeType> {
+    match current {
+        None => Some(ClozeType::ALL[0]),
+        Some(kind) => {
+            let pos = ClozeType::ALL.iter().position(|other| *other == kind);
+            pos.and_then(|pos| ClozeType::ALL.get(pos + 1).copied())
+        }
+    }
+}
+
+fn open_prompt(app: &mut App, prompt: Prompt, initial: &str) {
+    app.prompt = Some((prompt, initial.to_string()));
+}
+
+fn handle_key(app: &mut App, bank: &mut Bank, code: KeyCode) -> Action {
+    let armed = mem::take(&mut app.confirm_delete);
+    match (app.pane, code) {
+        (_, KeyCode::Char('q')) => return Action::Quit,
+        (_, KeyCode::Tab) if !bank.questions.is_empty() => {
+            app.pane = match app.pane {
+                Pane::Bank => Pane::Chunks,
+                Pane::Chunks => {
+                    prepare_answers(&mut bank.questions[app.question]);
+                    Pane::Options
+                }
+                Pane::Options => Pane::Bank,
+            }
+        }
+
+        (Pane::Bank, KeyCode::Up) => app.question = app.question.saturating_sub(1),
+        (Pane::Bank, KeyCode::Down) => app.question += 1,
+        (Pane::Bank, KeyCode::Char('n')) => open_prompt(app, Prompt::NewQuestion, ""),
+        (Pane::Bank, KeyCode::Char('d')) if !bank.questions.is_empty() => {
+            if armed {
+                bank.questions.remove(app.question);
+                app.message = "Question deleted.".to_string();
+            } else {
+                app.confirm_delete = true;
+                app.message = "Press d again to delete this question.".to_string();
+            }
+        }
+        (Pane::Bank, KeyCode::Char('[')) if app.question > 0 => {
+            bank.questions.swap(app.question - 1, app.question);
+            app.question -= 1;
+        }
+        (Pane::Bank, KeyCode::Char(']')) if app.question + 1 < bank.questions.len() => {
+            bank.questions.swap(app.question, app.question + 1);
+            app.question += 1;
+        }
+        (Pane::Bank, KeyCode::Enter) | (Pane::Bank, KeyCode::Right)
+            if !bank.questions.is_empty() =>
+        {
+            app.pane = Pane::Chunks;
+            app.chunk = 0;
+        }
+
+        (Pane::Chunks, KeyCode::Up) => app.chunk = app.chunk.saturating_sub(1),
+        (Pane::Chunks, KeyCode::Down) => app.chunk += 1,
+        (Pane::Chunks, KeyCode::Char('j')) => {
+            if app.chunk + 1 < chunk_count(&bank.questions, app) {
+                let result = apply_join(&mut bank.questions[app.question], app.chunk);
+                app.message = chunk_edit_message(result, "Chunks joined.");
+            } else {
+                app.message = "There is no next chunk to join.".to_string();
+            }
+        }
+        (Pane::Chunks, KeyCode::Char('[')) if app.chunk > 0 => {
+            let result = move_up(&mut bank.questions[app.question], app.chunk);
+            app.message = chunk_edit_message(result, "Chunk moved.");
+            app.chunk -= 1;
+        }
+        (Pane::Chunks, KeyCode::Char(']')) if app.chunk + 1 < chunk_count(&bank.questions, app) => {
+            let result = apply_reorder(&mut bank.questions[app.question], app.chunk);
+            app.message = chunk_edit_message(result, "Chunk moved.");
+            app.chunk += 1;
+        }
+        (Pane::Chunks, KeyCode::Char('r')) => {
+            let note = revert_splits(&mut bank.questions[app.question]);
+            app.message = chunk_edit_message(Ok(note), "Last change reverted.");
+        }
+        (Pane::Chunks, KeyCode::Char('t')) if app.chunk < chunk_count(&bank.questions, app) => {
+            let sentence = &mut bank.questions[app.question];
+            prepare_answers(sentence);
+            let settings = &mut sentence.chunk_settings[app.chunk];
+            settings.gap_type = next_gap_type(settings.gap_type);
+            app.message = match settings.gap_type {
+                Some(kind) => format!("Gap type: {}", kind.label()),
+                None => format!("Gap type: bank default ({})", bank.default_gap_type.label()),
+            };
+        }
+        (Pane::Chunks, KeyCode::Char('w')) if app.chunk < chunk_count(&bank.questions, app) => {
+            let sentence = &mut bank.questions[app.question];
+            prepare_answers(sentence);
+            let current = sentence.chunk_settings[app.chunk]
+                .weight
+                .map_or(String::new(), |weight| weight.to_string());
+            open_prompt(app, Prompt::Weight, &current);
+        }
+        (Pane::Chunks, KeyCode::Char('g')) if app.chunk < chunk_count(&bank.questions, app) => {
+            let sentence = &mut bank.questions[app.question];
+            prepare_answers(sentence);
+            let settings = &mut sentence.chunk_settings[app.chunk];
+            settings.category = match settings.category {
+                None => Some(Category::ALL[0]),
+                Some(category) => {
+                    let pos = Category::ALL.iter().position(|other| *other == category);
+                    pos.and_then(|pos| Category::ALL.get(pos + 1).copied())
+                }
+            };
+            app.message = match settings.category {
+                Some(category) => format!("Category: {}", category.label()),
+                None => "Category: none".to_string(),
+            };
+        }
+        (Pane::Chunks, KeyCode::Char('c')) => {
+            let sentence = &mut bank.questions[app.question];
+            if can_complete(sentence) {
+                sentence.completed = true;
+                app.message = "Question marked complete.".to_string();
+            } else {
+                app.message = "You haven't entered any answers.".to_string();
+            }
+        }
+        (Pane::Chunks, KeyCode::Enter) | (Pane::Chunks, KeyCode::Right) => {
+            prepare_answers(&mut bank.questions[app.question]);
+            app.pane = Pane::Options;
+            app.option = 0;
+        }
+        (Pane::Chunks, KeyCode::Left) | (Pane::Chunks, KeyCode::Esc) => app.pane = Pane::Bank,
+
+        (Pane::Options, KeyCode::Up) => app.option = app.option.saturating_sub(1),
+        (Pane::Options, KeyCode::Down) => app.option += 1,
+        (Pane::Options, KeyCode::Char('a')) => open_prompt(app, Prompt::Answer, ""),
+        (Pane::Options, _) if option_count(&bank.questions, app) == 0 => {
+            if let KeyCode::Left | KeyCode::Esc = code {
+                app.pane = Pane::Chunks;
+            }
+        }
+        (Pane::Options, KeyCode::Char('e')) => {
+            let current = bank.questions[app.question].answers[app.chunk][app.option]
+                .answer
+                .clone();
+            open_prompt(app, Prompt::EditAnswer(app.option), &current);
+        }
+        (Pane::Options, KeyCode::Char('m')) => {
+            let current = bank.questions[app.question].answers[app.chunk][app.option].mark;
+            open_prompt(app, Prompt::EditMark(app.option), &current.to_string());
+        }
+        (Pane::Options, KeyCode::Char('f')) => {
+            let current = bank.questions[app.question].answers[app.chunk][app.option]
+                .feedback
+                .clone();
+            open_prompt(app, Prompt::EditFeedback(app.option), &current);
+        }
+        (Pane::Options, KeyCode::Char('x')) => {
+            mark_non_question(&mut bank.questions[app.question], app.chunk, app.option)
+        }
+        (Pane::Options, KeyCode::Char('d')) => {
+            if armed {
+                delete_answer(&mut bank.questions[app.question], app.chunk, app.option);
+                app.message = "Answer deleted.".to_string();
+            } else {
+                app.confirm_delete = true;
+                app.message = "Press d again to delete this answer.".to_string();
+            }
+        }
+        (Pane::Options, KeyCode::Left) | (Pane::Options, KeyCode::Esc) => app.pane = Pane::Chunks,
+        _ => {}
+    }
+    Action::Continue
+}
+
+fn handle_prompt(app: &mut App, bank: &mut Bank, code: KeyCode) {
+    let (prompt, mut buffer) = match app.prompt.take() {
+        Some(prompt) => prompt,
+        None => return,
+    };
+    match code {
+        KeyCode::Esc => return,
+        KeyCode::Enter => {}
+        KeyCode::Backspace => {
+            buffer.pop();
+            app.prompt = Some((prompt, buffer));
+            return;
+        }
+        KeyCode::Char(c) => {
+            buffer.push(c);
+            app.prompt = Some((prompt, buffer));
+            return;
+        }
+        _ => {
+            app.prompt = Some((prompt, buffer));
+            return;
+        }
+    }
+
+    let mark = buffer.trim().parse::<u8>().ok().filter(|mark| *mark <= 100);
+    match prompt {
+        Prompt::NewQuestion => {
+            if !buffer.trim().is_empty() {
+                bank.add(Sentence::new(buffer));
+                app.question = bank.questions.len() - 1;
+            }
+        }
+        Prompt::Answer => {
+            let initial = if option_count(&bank.questions, app) == 0 {
+                "100"
+            } else {
+                "0"
+            };
+            open_prompt(app, Prompt::Mark(buffer), initial);
+        }
+        Prompt::Mark(answer) => match mark {
+            Some(mark) => {
+                let initial = if mark == 100 {
+                    "Well done!"
+                } else {
+                    "Try again!"
+                };
+                open_prompt(app, Prompt::Feedback(answer, mark), initial);
+            }
+            None => {
+                app.message = "Marks must be between 0 and 100.".to_string();
+                open_prompt(app, Prompt::Mark(answer), &buffer);
+            }
+        },
+        Prompt::Feedback(answer, mark) => {
+            bank.questions[app.question].answers[app.chunk].push(AnswerOption {
+                is_question: true,
+                mark,
+                answer,
+                feedback: buffer,
+            });
+            app.option = option_count(&bank.questions, app) - 1;
+        }
+        Prompt::EditAnswer(opt) => {
+            bank.questions[app.question].answers[app.chunk][opt].answer = buffer
+        }
+        Prompt::EditMark(opt) => match mark {
+            Some(mark) => bank.questions[app.question].answers[app.chunk][opt].mark = mark,
+            None => {
+                app.message = "Marks must be between 0 and 100.".to_string();
+                open_prompt(app, Prompt::EditMark(opt), &buffer);
+            }
+        },
+        Prompt::EditFeedback(opt) => {
+            bank.questions[app.question].answers[app.chunk][opt].feedback = buffer
+        }
+        Prompt::Weight => {
+            let settings = &mut bank.questions[app.question].chunk_settings[app.chunk];
+            match buffer.trim() {
+                "" => settings.weight = None,
+                text => match text.parse::<u32>() {
+                    Ok(weight) if weight > 0 => settings.weight = Some(weight),
+                    _ => {
+                        app.message = "Weights must be whole numbers above 0.".to_string();
+                        open_prompt(app, Prompt::Weight, &buffer);
+                    }
+                },
+            }
+        }
+    }
+}
+
+fn pane_block(title: &str, focused: bool) -> Block<'_> {
+    let block = Block::bordered().title(title);
+    if focused {
+        block.border_style(Style::new().add_modifier(Modifier::BOLD))
+    } else {
+        block
+    }
+}
+
+fn draw_list(frame: &mut Frame, area: Rect, block: Block, items: Vec<String>, selected: usize) {
+    let items: Vec<ListItem> = items.into_iter().map(ListItem::new).collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw(frame: &mut Frame, app: &App, bank: &Bank) {
+    let [main, preview, footer] = Layout::vertical([
+        Constraint::Min(6),
+        Constraint::Length(6),
+        Constraint::Length(3),
+    ])
+    .areas(frame.area());
+    let [bank_area, editor] =
+        Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(main);
+    let [chunk_area, option_area] =
+        Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(editor);
+
+    let questions: Vec<String> = bank
+        .questions
+        .iter()
+        .enumerate()
+        .map(|(num, question)| {
+            let status = if question.completed { "D" } else { "i" };
+            format!("{} {}. {}", status, num + 1, question.initial_sentence)
+        })
+        .collect();
+    draw_list(
+        frame,
+        bank_area,
+        pane_block("Bank", app.pane == Pane::Bank),
+        questions,
+        app.question,
+    );
+
+    let sentence = bank.questions.get(app.question);
+    let mut chunks: Vec<String> = Vec::new();
+    let mut options: Vec<String> = Vec::new();
+    if let Some(sentence) = sentence {
+        for (num, word) in sentence.splits[sentence.current_split].iter().enumerate() {
+            let count = sentence.answers.get(num).map_or(0, Vec::len);
+            let fixed = count > 0 && !sentence.answers[num][0].is_question;
+            let settings = sentence.chunk_settings.get(num);
+            let mut detail = if fixed {
+                "fixed text".to_string()
+            } else {
+                format!("{} answers, weight {}", count, sentence.gap_weight(num))
+            };
+            if let Some(kind) = settings.and_then(|settings| settings.gap_type) {
+                detail.push_str(&format!(", {}", kind.label()));
+            }
+            if let Some(category) = settings.and_then(|settings| settings.category) {
+                detail.push_str(&format!(", {}", category.label()));
+            }
+            chunks.push(format!("{}: {} ({})", num + 1, word, detail));
+        }
+        if let Some(group) = sentence.answers.get(app.chunk) {
+            for answer in group {
+                options.push(format!(
+                    "{:>3}% | {} | {}",
+                    answer.mark, answer.answer, answer.feedback
+                ));
+            }
+            if let Some(answer) = catch_all_option(bank, sentence, app.chunk) {
+                options.push(format!(
+                    "{:>3}% | {} | {} (added at export)",
+                    answer.mark, answer.answer, answer.feedback
+                ));
+            }
+        }
+    }
+    let chunk_title = match sentence {
+        Some(sentence) => format!("Chunks (total weight {})", sentence.total_weight()),
+        None => "Chunks".to_string(),
+    };
+    draw_list(
+        frame,
+        chunk_area,
+        pane_block(&chunk_title, app.pane == Pane::Chunks),
+        chunks,
+        app.chunk,
+    );
+    draw_list(
+        frame,
+        option_area,
+        pane_block("Answers", app.pane == Pane::Options),
+        options,
+        app.option,
+    );
+
+    let lines = match sentence {
+        Some(sentence) => vec![
+            Line::from(sentence.initial_sentence.clone()),
+            Line::from(cloze_preview(sentence, bank.default_gap_type)),
+            Line::from(format!("Model answer: {}", sentence.model_answer())),
+        ],
+        None => vec![Line::from("Press n to enter your first question...")],
+    };
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(Block::bordered().title("Preview"))
+            .wrap(Wrap { trim: false }),
+        preview,
+    );
+
+    let help = match &app.prompt {
+        Some((prompt, buffer)) => format!("{}: {}_", prompt.label(), buffer),
+        None => {
+            let keys = match app.pane {
+                Pane::Bank => "New: n  Delete: d d  Move: [ ]  Open: RET  Quit: q",
+                Pane::Chunks => {
+                    "Join: j  Move: [ ]  Revert: r  Type: t  Weight: w  Category: g  Complete: c  Answers: RET  Back: ESC"
+                }
+                Pane::Options => {
+                    "Add: a  Edit: e  Mark: m  Feedback: f  Non-question: x  Delete: d d  Back: ESC"
+                }
+            };
+            if app.message.is_empty() {
+                keys.to_string()
+            } else {
+                format!("{}  |  {}", app.message, keys)
+            }
+        }
+    };
+    frame.render_widget(Paragraph::new(help).block(Block::bordered()), footer);
+}